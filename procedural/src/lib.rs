@@ -1,18 +1,206 @@
+mod map_migration;
+
 use quote::{quote, ToTokens};
 use std::collections::BTreeMap;
 use syn::{parse_macro_input, parse_quote};
 
 const ON_IDLE_HOOK: &str = "on_idle";
 const INTEGRITY_TEST_HOOK: &str = "integrity_test";
+const TRY_STATE_HOOK: &str = "try_state";
+const DEFAULT_MIGRATION_IDENT: &str = "Migration";
+const MIGRATE_CALL_INDEX: u8 = 254;
+/// The call indices of the migration dispatchables, see `generate_calls`.
+const MIGRATION_CALL_INDICES: core::ops::RangeInclusive<u8> = 250..=255;
+
+fn default_migration_ident() -> syn::Ident {
+    syn::Ident::new(DEFAULT_MIGRATION_IDENT, proc_macro2::Span::call_site())
+}
+
+/// Parses the `struct = <ident>` argument shared by the `pallet` and `hooks` macros, which names
+/// the generated migration struct.
+fn parse_struct_arg(meta: &syn::meta::ParseNestedMeta) -> syn::Result<Option<syn::Ident>> {
+    if meta.path.is_ident("struct") {
+        Ok(Some(meta.value()?.parse()?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Arguments accepted by the `hooks` macro.
+struct HooksArgs {
+    /// The name of the migration struct generated by the `pallet` macro.
+    migration_ident: syn::Ident,
+    /// The weight of the user's own `on_idle` body, kept out of the reach of the migrations.
+    reserve_for_user: Option<syn::Expr>,
+    /// Whether the user's own `on_idle` body runs before the migrations.
+    user_first: bool,
+}
+
+impl Default for HooksArgs {
+    fn default() -> Self {
+        Self {
+            migration_ident: default_migration_ident(),
+            reserve_for_user: None,
+            user_first: false,
+        }
+    }
+}
+
+impl HooksArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if let Some(ident) = parse_struct_arg(&meta)? {
+            self.migration_ident = ident;
+            Ok(())
+        } else if meta.path.is_ident("reserve_for_user") {
+            self.reserve_for_user = Some(meta.value()?.parse()?);
+            Ok(())
+        } else if meta.path.is_ident("order") {
+            let order: syn::Ident = meta.value()?.parse()?;
+            self.user_first = match order.to_string().as_str() {
+                "user_first" => true,
+                "migrations_first" => false,
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        order,
+                        "expected `user_first` or `migrations_first`",
+                    ))
+                }
+            };
+            Ok(())
+        } else {
+            Err(meta.error("unsupported migratable::hooks argument"))
+        }
+    }
+}
+
+/// Implements `Default` and `MigrationStep` for a migration moving the entries of one
+/// `StorageMap` into another, one entry per step.
+///
+/// The struct must have a `last_key: Option<Vec<u8>>` field, which holds the raw key of the last
+/// migrated entry so that the iteration resumes from it, and any other field must implement
+/// `Default`. The migration is configured through the `map_migration` attribute:
+/// - `version`: the version of the step.
+/// - `old`: the map to migrate from, which must use a reversible hasher.
+/// - `new`: the map to migrate to, which must not share its prefix with `old`.
+/// - `transform`: a function taking the key and value of an `old` entry, and returning the key
+///   and value to insert into `new`, or `None` to drop the entry.
+/// - `weight`: the weight of a single step.
+/// - `max_key_len`: the maximum length of a raw key of `old` stored in the cursor.
+///
+/// ```ignore
+/// #[derive(Encode, Decode, MapMigration)]
+/// #[map_migration(
+///     version = 2,
+///     old = v1::Ledger<T>,
+///     new = Ledger<T>,
+///     transform = v1::into_ledger::<T>,
+///     weight = T::DbWeight::get().reads_writes(1, 2),
+///     max_key_len = 80,
+/// )]
+/// pub struct MigrateLedger<T: Config> {
+///     last_key: Option<Vec<u8>>,
+///     _phantom: PhantomData<T>,
+/// }
+/// ```
+#[proc_macro_derive(MapMigration, attributes(map_migration))]
+pub fn map_migration(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as syn::DeriveInput);
+    map_migration::expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Returns the name of the remaining weight parameter of the `on_idle` hook, after checking that
+/// the hook takes the block number and the remaining weight.
+fn remaining_weight_ident(sig: &syn::Signature) -> syn::Result<syn::Ident> {
+    let mut inputs = sig.inputs.iter();
+    let (Some(syn::FnArg::Typed(block)), Some(syn::FnArg::Typed(remaining_weight)), None) =
+        (inputs.next(), inputs.next(), inputs.next())
+    else {
+        return Err(syn::Error::new_spanned(
+            sig,
+            "`on_idle` must take the block number and the remaining weight as parameters",
+        ));
+    };
+    if !is_path_to(&block.ty, &["BlockNumberFor", "BlockNumber"]) {
+        return Err(syn::Error::new_spanned(
+            &block.ty,
+            "the first parameter of `on_idle` must be the block number, e.g. `BlockNumberFor<T>`",
+        ));
+    }
+    if !is_path_to(&remaining_weight.ty, &["Weight"]) {
+        return Err(syn::Error::new_spanned(
+            &remaining_weight.ty,
+            "the second parameter of `on_idle` must be the remaining `Weight`",
+        ));
+    }
+    match &*remaining_weight.pat {
+        syn::Pat::Ident(pat) => Ok(pat.ident.clone()),
+        pat => Err(syn::Error::new_spanned(
+            pat,
+            "the remaining weight parameter of `on_idle` must be bound to a name",
+        )),
+    }
+}
+
+/// Whether `ty` is a path whose last segment is one of `names`, regardless of its generics.
+fn is_path_to(ty: &syn::Type, names: &[&str]) -> bool {
+    match ty {
+        syn::Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| names.iter().any(|name| segment.ident == name)),
+        _ => false,
+    }
+}
 
+/// Injects the migration driver into the pallet's `on_idle` and `integrity_test` hooks, and the
+/// consistency checks of the migration storage into its `try_state` hook.
+///
+/// The generated `on_idle` runs the migrations first, and then the user's own hook with the weight
+/// they left. The weight it returns is the weight of the user's own hook plus the sum of the
+/// weights reported by every `Migration::migrate` call, so it never under-reports the weight
+/// consumed by migrations even if a call overruns the remaining weight.
+///
+/// A user's hook that needs some weight guaranteed can reserve it with `reserve_for_user`, e.g.
+/// `#[migratable::hooks(reserve_for_user = T::DbWeight::get().reads_writes(10, 10))]`. The
+/// reservation, capped at the remaining weight, is kept out of the reach of the migrations and
+/// given back to the user's hook.
+///
+/// With `order = user_first`, the user's hook runs first instead, with the whole remaining weight,
+/// and the migrations get what it leaves, i.e. the remaining weight minus the weight it returns.
+/// `order = migrations_first` is the default, and the only order `reserve_for_user` applies to.
+///
+/// If the migration struct was renamed in the `pallet` macro, the same name must be given here,
+/// e.g. `#[migratable::hooks(struct = Migrator)]`.
 #[proc_macro_attribute]
 pub fn hooks(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let mut input = parse_macro_input!(item as syn::ItemImpl);
+    let mut args = HooksArgs::default();
+    let args_parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attr with args_parser);
+    let input = parse_macro_input!(item as syn::ItemImpl);
+    expand_hooks(&args, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_hooks(
+    args: &HooksArgs,
+    mut input: syn::ItemImpl,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let migration_ident = &args.migration_ident;
+    if let (true, Some(reserved)) = (args.user_first, &args.reserve_for_user) {
+        return Err(syn::Error::new_spanned(
+            reserved,
+            "`reserve_for_user` only applies when the migrations run first",
+        ));
+    }
     let custom_code_set: sp_std::collections::btree_set::BTreeSet<&str> =
-        [ON_IDLE_HOOK, INTEGRITY_TEST_HOOK]
+        [ON_IDLE_HOOK, INTEGRITY_TEST_HOOK, TRY_STATE_HOOK]
             .iter()
             .cloned()
             .collect();
@@ -46,6 +234,18 @@ pub fn hooks(
             .items
             .push(parse_quote! { #default_integrity_test_hook });
     }
+    // add default try_state implementation if needed
+    if !implemented_fn.contains_key(TRY_STATE_HOOK) {
+        let default_try_state_hook = quote!(
+            #[cfg(feature = "try-runtime")]
+            fn try_state(
+                _block: frame_system::pallet_prelude::BlockNumberFor<T>,
+            ) -> Result<(), sp_runtime::TryRuntimeError> {
+                Ok(())
+            }
+        );
+        input.items.push(parse_quote! { #default_try_state_hook });
+    }
     // modify the actual hooks
     for item in &mut input.items {
         if let syn::ImplItem::Fn(method) = item {
@@ -55,54 +255,44 @@ pub fn hooks(
                 match fn_name.as_str() {
                     ON_IDLE_HOOK => {
                         // get the second attribute's name
-                        if let Some(syn::FnArg::Typed(remaining_weight_attr)) =
-                            method.sig.inputs.last()
-                        {
-                            if let syn::Pat::Ident(remaining_weight_ident) =
-                                *remaining_weight_attr.pat.clone()
-                            {
-                                let remaining_weight_name = remaining_weight_ident.ident;
-                                let new_code = quote!(
-                                    {
-                                        let mut weight_sum = #remaining_weight_name.clone();
-                                        loop {
-                                            let (result, weight) = Migration::<T>::migrate(#remaining_weight_name);
-                                            #remaining_weight_name.saturating_reduce(weight);
-
-                                            match result {
-                                                // There is not enough weight to perform a migration, or make any progress, we
-                                                // just return the remaining weight.
-                                                migratable::MigrateResult::NoMigrationPerformed | migratable::MigrateResult::InProgress { steps_done: 0 } => return #remaining_weight_name,
-                                                // Migration is still in progress, we can start the next step.
-                                                migratable::MigrateResult::InProgress { .. } => continue,
-                                                // Either no migration is in progress, or we are done with all migrations, we
-                                                // can do some more other work with the remaining weight.
-                                                migratable::MigrateResult::Completed | migratable::MigrateResult::NoMigrationInProgress => break,
-                                            }
-                                        };
-                                        weight_sum.saturating_reduce(#remaining_weight_name);
-                                        weight_sum
-                                    }
-                                );
-                                // mutate the block to include the new code
-                                method.block = parse_quote! {
-                                    {
-                                        let migration_weight = #new_code;
-                                        let mut weight = #curr_impl;
-                                        weight.saturating_add(migration_weight);
-                                        weight
-                                    }
-                                };
-                            } else {
-                                panic!("on_idle hook does not have the second parameter correctly defined");
+                        let remaining_weight_name = remaining_weight_ident(&method.sig)?;
+                        let new_code = match &args.reserve_for_user {
+                            Some(reserved) => quote!(
+                                migratable::with_reserved_weight(
+                                    &mut #remaining_weight_name,
+                                    #reserved,
+                                    #migration_ident::<T>::migrate_idle,
+                                )
+                            ),
+                            None => quote!(
+                                #migration_ident::<T>::migrate_idle(&mut #remaining_weight_name)
+                            ),
+                        };
+                        // mutate the block to include the new code
+                        method.block = if args.user_first {
+                            parse_quote! {
+                                {
+                                    let remaining_weight_before = #remaining_weight_name;
+                                    let weight = #curr_impl;
+                                    #remaining_weight_name =
+                                        remaining_weight_before.saturating_sub(weight);
+                                    let migration_weight = #new_code;
+                                    weight.saturating_add(migration_weight)
+                                }
                             }
                         } else {
-                            panic!("on_idle hook is not properly defined");
-                        }
+                            parse_quote! {
+                                {
+                                    let migration_weight = #new_code;
+                                    let weight = #curr_impl;
+                                    weight.saturating_add(migration_weight)
+                                }
+                            }
+                        };
                     }
                     INTEGRITY_TEST_HOOK => {
                         let new_code = quote!(
-                            Migration::<T>::integrity_test();
+                            #migration_ident::<T>::integrity_test();
                         );
                         method.block = parse_quote! {
                             {
@@ -111,57 +301,477 @@ pub fn hooks(
                             }
                         };
                     }
+                    TRY_STATE_HOOK => {
+                        method.block = parse_quote! {
+                            {
+                                #migration_ident::<T>::try_state()?;
+                                #curr_impl
+                            }
+                        };
+                    }
                     _ => {}
                 }
             } else {
-                panic!("\"{}\" not found in pallet hooks", fn_name);
+                return Err(syn::Error::new_spanned(
+                    &method.sig.ident,
+                    format!("`{fn_name}` is not supported by `migratable::hooks`, expected `{ON_IDLE_HOOK}`, `{INTEGRITY_TEST_HOOK}` or `{TRY_STATE_HOOK}`"),
+                ));
             }
         }
     }
-    let modified_impl = quote! {
+    Ok(quote! {
         #input
-    };
-    modified_impl.into()
+    })
 }
 
-/// Adds the `Migrations` type to `Config`
+/// Adds the `Migrations` type, the `on_migrations_completed` hook, the `TARGET_VERSION` cap, the
+/// `MigrationObserver` type, the `max_migrate_call_weight` cap, the `min_idle_weight_for_migration`
+/// threshold, the `OFFCHAIN_INDEX_PROGRESS` flag, the `migrations_enabled` kill switch, the
+/// `on_no_migration` hook and the `MAX_STEP_FAILURES` limit to `Config`
+///
+/// They are appended after the items of the trait, whose generics, supertraits and where clause
+/// are kept as is. The trait must not declare any of them itself.
 #[proc_macro_attribute]
 pub fn config(
     _attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
     let mut input = parse_macro_input!(item as syn::ItemTrait);
+    // the items of the user, which the injected ones are appended after, leaving the generics,
+    // supertraits and where clause of the trait untouched
+    let user_items = input.items.len();
     let migrations = quote!(
         /// The sequence of migration steps that will be applied during a migration.
         type Migrations: migratable::MigrateSequence;
     );
     input.items.push(parse_quote! { #migrations });
+    let on_migrations_completed = quote!(
+        /// Called once all the `Migrations` are completed, atomically with the removal of the
+        /// migration cursor.
+        ///
+        /// Returns the weight consumed.
+        fn on_migrations_completed() -> frame_support::weights::Weight {
+            frame_support::weights::Weight::zero()
+        }
+    );
+    input.items.push(parse_quote! { #on_migrations_completed });
+    let target_version = quote!(
+        /// Caps the migrations applied by this runtime at the given version, even if `Migrations`
+        /// reaches further. `None` migrates up to the current storage version of the pallet.
+        const TARGET_VERSION: Option<frame_support::traits::StorageVersion> = None;
+    );
+    input.items.push(parse_quote! { #target_version });
+    let migration_observer = quote!(
+        /// Notified of the progress of the migrations. Use `()` to ignore it.
+        type MigrationObserver: migratable::MigrationObserver;
+    );
+    input.items.push(parse_quote! { #migration_observer });
+    let max_migrate_call_weight = quote!(
+        /// The maximum weight of migration steps a single generated `migrate` call can run, which
+        /// is also the worst case it declares. Defaults to the whole block.
+        fn max_migrate_call_weight() -> frame_support::weights::Weight {
+            <<Self as frame_system::Config>::BlockWeights as frame_support::traits::Get<
+                frame_system::limits::BlockWeights,
+            >>::get()
+            .max_block
+        }
+    );
+    input.items.push(parse_quote! { #max_migrate_call_weight });
+    let min_idle_weight_for_migration = quote!(
+        /// The remaining weight below which `on_idle` doesn't run migrations, so that they are
+        /// batched into blocks with substantial idle weight. Defaults to the overhead of `migrate`.
+        fn min_idle_weight_for_migration() -> frame_support::weights::Weight {
+            <migratable::weights::SubstrateWeight<Self> as migratable::weights::WeightInfo>::migrate(
+            )
+        }
+    );
+    input
+        .items
+        .push(parse_quote! { #min_idle_weight_for_migration });
+    let offchain_index_progress = quote!(
+        /// Whether `migrate` writes a `migratable::MigrationProgressRecord` to the off-chain
+        /// indexing storage, keyed by block number, every time it runs migration steps.
+        const OFFCHAIN_INDEX_PROGRESS: bool = false;
+    );
+    input.items.push(parse_quote! { #offchain_index_progress });
+    let migrations_enabled = quote!(
+        /// A kill switch for all migration activity: while it returns `false`, `migrate` runs no
+        /// step and returns `NoMigrationPerformed`.
+        ///
+        /// Runtimes can override it in every pallet to read a single storage value, charged as one
+        /// read, e.g. `MigrationsEnabled::get()`, so that governance can halt the migrations of
+        /// all the pallets at once.
+        fn migrations_enabled() -> bool {
+            true
+        }
+    );
+    input.items.push(parse_quote! { #migrations_enabled });
+    let on_no_migration = quote!(
+        /// Called by `migrate` when no migration is in progress. Returning the version following
+        /// the on-chain storage version starts its migration right away, e.g. to recover from a
+        /// migration `on_runtime_upgrade` didn't start.
+        ///
+        /// It is called on every idle block once the migrations are done, so it must be cheap: its
+        /// weight is not charged beyond the `migration_noop` weight.
+        fn on_no_migration() -> Option<frame_support::traits::StorageVersion> {
+            None
+        }
+    );
+    input.items.push(parse_quote! { #on_no_migration });
+    let max_step_failures = quote!(
+        /// The number of transactional steps failing in a row, see `MigrationStep::try_step`,
+        /// after which the migration is halted until its cursor is replaced, e.g. through the
+        /// generated `force_set_cursor` call. The calls of the pallet stay blocked meanwhile.
+        const MAX_STEP_FAILURES: u32 = 10;
+    );
+    input.items.push(parse_quote! { #max_step_failures });
+    let (user_items, injected_items) = input.items.split_at(user_items);
+    for injected in injected_items.iter().filter_map(trait_item_ident) {
+        if let Some(declared) = user_items
+            .iter()
+            .filter_map(trait_item_ident)
+            .find(|declared| *declared == injected)
+        {
+            return syn::Error::new_spanned(
+                declared,
+                format!("`{declared}` is added to `Config` by `migratable::config`, remove it"),
+            )
+            .into_compile_error()
+            .into();
+        }
+    }
     let output = quote! {
         #input
     };
     output.into()
 }
 
+/// Returns the name of a type, const or fn item of a trait.
+fn trait_item_ident(item: &syn::TraitItem) -> Option<&syn::Ident> {
+    match item {
+        syn::TraitItem::Type(item) => Some(&item.ident),
+        syn::TraitItem::Const(item) => Some(&item.ident),
+        syn::TraitItem::Fn(item) => Some(&item.sig.ident),
+        _ => None,
+    }
+}
+
+/// Arguments accepted by the `pallet` macro.
+struct PalletArgs {
+    /// Whether to generate the `migration_status` view function.
+    view_functions: bool,
+    /// Whether to generate the migration dispatchables.
+    calls: bool,
+    /// Whether to store the cursor in a `MigrationState` instead of `MigrationInProgress`.
+    state: bool,
+    /// Whether to expose the weights of the migrations as pallet constants.
+    constants: bool,
+    /// Whether to deposit the migration events, added to the `#[pallet::event]` enum of the pallet.
+    events: bool,
+    /// The name of the generated migration struct.
+    migration_ident: syn::Ident,
+}
+
+impl Default for PalletArgs {
+    fn default() -> Self {
+        Self {
+            view_functions: false,
+            calls: false,
+            state: false,
+            constants: false,
+            events: false,
+            migration_ident: default_migration_ident(),
+        }
+    }
+}
+
+impl PalletArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if let Some(ident) = parse_struct_arg(&meta)? {
+            self.migration_ident = ident;
+            Ok(())
+        } else if meta.path.is_ident("view_functions") {
+            self.view_functions = true;
+            Ok(())
+        } else if meta.path.is_ident("state") {
+            self.state = true;
+            Ok(())
+        } else if meta.path.is_ident("calls") {
+            self.calls = true;
+            Ok(())
+        } else if meta.path.is_ident("constants") {
+            self.constants = true;
+            Ok(())
+        } else if meta.path.is_ident("events") {
+            self.events = true;
+            Ok(())
+        } else {
+            Err(meta.error("unsupported migratable::pallet argument"))
+        }
+    }
+}
+
+/// Whether `attrs` contain the `#[pallet::<name>]` attribute.
+fn has_pallet_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        let segments = &attr.path().segments;
+        segments.len() == 2 && segments[0].ident == "pallet" && segments[1].ident == name
+    })
+}
+
+/// Appends `items` to the `#[pallet::<attr>]` impl block of the pallet, creating it if needed.
+fn add_to_pallet_impl(content: &mut Vec<syn::Item>, attr: &str, items: proc_macro2::TokenStream) {
+    let attr_ident = syn::Ident::new(attr, proc_macro2::Span::call_site());
+    let items: syn::ItemImpl = parse_quote! {
+        #[pallet::#attr_ident]
+        impl<T: Config> Pallet<T> {
+            #items
+        }
+    };
+    let pallet_impl = content.iter_mut().find_map(|item| match item {
+        syn::Item::Impl(item_impl) if has_pallet_attr(&item_impl.attrs, attr) => Some(item_impl),
+        _ => None,
+    });
+    match pallet_impl {
+        Some(pallet_impl) => pallet_impl.items.extend(items.items),
+        None => content.push(syn::Item::Impl(items)),
+    }
+}
+
+/// Appends `calls` to the `#[pallet::call]` impl block of the pallet, creating it if needed.
+///
+/// Fails if a call of the pallet already uses one of the call indices of the migration calls.
+fn add_calls(content: &mut Vec<syn::Item>, calls: proc_macro2::TokenStream) -> syn::Result<()> {
+    let user_calls = content.iter().filter_map(|item| match item {
+        syn::Item::Impl(item_impl) if has_pallet_attr(&item_impl.attrs, "call") => Some(item_impl),
+        _ => None,
+    });
+    for item in user_calls.flat_map(|item_impl| &item_impl.items) {
+        let syn::ImplItem::Fn(call) = item else {
+            continue;
+        };
+        for attr in &call.attrs {
+            let segments = &attr.path().segments;
+            if segments.len() != 2
+                || segments[0].ident != "pallet"
+                || segments[1].ident != "call_index"
+            {
+                continue;
+            }
+            let index: syn::LitInt = attr.parse_args()?;
+            if MIGRATION_CALL_INDICES.contains(&index.base10_parse::<u8>()?) {
+                return Err(syn::Error::new_spanned(
+                    index,
+                    format!(
+                        "call index reserved for the migration calls of `migratable::pallet(calls)`, \
+                         which use the indices {} to {}",
+                        MIGRATION_CALL_INDICES.end(),
+                        MIGRATION_CALL_INDICES.start(),
+                    ),
+                ));
+            }
+        }
+    }
+    add_to_pallet_impl(content, "call", calls);
+    Ok(())
+}
+
+/// Appends the migration events to the `#[pallet::event]` enum of the pallet, returning the path
+/// to build them with, e.g. `Event::<T>`.
+fn add_events(
+    content: &mut [syn::Item],
+    pallet: &syn::Ident,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let Some(event) = content.iter_mut().find_map(|item| match item {
+        syn::Item::Enum(item_enum) if has_pallet_attr(&item_enum.attrs, "event") => Some(item_enum),
+        _ => None,
+    }) else {
+        return Err(syn::Error::new_spanned(
+            pallet,
+            "the `events` argument of `migratable::pallet` requires a `#[pallet::event]` enum",
+        ));
+    };
+    let events: syn::ItemEnum = parse_quote! {
+        enum Events {
+            /// The `MigrationStep::verify` checks of `version` passed.
+            MigrationVerified { version: u16 },
+            /// Entries of `MigrationDurations` were removed. The removal can be resumed from
+            /// `maybe_cursor`, if any entry is left.
+            MigrationHistoryCleared {
+                removed: u32,
+                maybe_cursor: Option<frame_support::sp_std::vec::Vec<u8>>,
+            },
+        }
+    };
+    event.variants.extend(events.variants);
+    let event_ident = &event.ident;
+    Ok(if event.generics.params.is_empty() {
+        quote!(#event_ident)
+    } else {
+        quote!(#event_ident::<T>)
+    })
+}
+
 /// Adds the following to the pallet module:
-/// - `MigrationInProgress` storage item.
-/// -
+/// - `MigrationInProgress` storage item, or `MigrationState` if the `state` argument is given,
+///   e.g. `#[migratable::pallet(state)]`, to tell pending migrations apart from completed ones.
+/// - `MigrationWeightConsumed` storage item.
+/// - `MigrationStepsDone` storage item.
+/// - `MigrationStepFailures` storage item, halting a migration whose steps keep failing.
+/// - `MigrationStepsInBlock` storage item, enforcing `MigrationStep::max_steps_per_block` across
+///   the `migrate` calls of a block.
+/// - `MigrationStartedAt` and `MigrationDurations` storage items, tracking how many blocks each
+///   version took.
+/// - `MigrationStartBlock` storage item, scheduling the start of the migrations.
+/// - `Migration` struct, which drives the migrations of `Config::Migrations`. It can be renamed
+///   with the `struct` argument, e.g. `#[migratable::pallet(struct = Migrator)]`.
+/// - `migration_status` view function, if the `view_functions` argument is given, e.g.
+///   `#[migratable::pallet(view_functions)]`.
+/// - `migrate_weight`, `migration_noop_weight` and `max_migrate_call_weight` pallet constants, if
+///   the `constants` argument is given, e.g. `#[migratable::pallet(constants)]`.
+/// - Dispatchables to operate the migrations, if the `calls` argument is given. They are appended
+///   to the `#[pallet::call]` block of the pallet, using call indices from 255 downwards.
+/// - Migration events, if the `events` argument is given. They are appended to the
+///   `#[pallet::event]` enum of the pallet, whose `Config` must declare the usual `RuntimeEvent`.
 #[proc_macro_attribute]
 pub fn pallet(
-    _attr: proc_macro::TokenStream,
+    attr: proc_macro::TokenStream,
     item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-    let mut input = parse_macro_input!(item as syn::ItemMod);
-    let content = &mut input.content.as_mut().unwrap().1;
+    let mut args = PalletArgs::default();
+    let args_parser = syn::meta::parser(|meta| args.parse(meta));
+    parse_macro_input!(attr with args_parser);
+    let input = parse_macro_input!(item as syn::ItemMod);
+    expand_pallet(&args, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_pallet(
+    args: &PalletArgs,
+    mut input: syn::ItemMod,
+) -> syn::Result<proc_macro2::TokenStream> {
+    let migration_ident = &args.migration_ident;
+    let Some((_, content)) = input.content.as_mut() else {
+        return Err(syn::Error::new_spanned(
+            &input,
+            "`migratable::pallet` must be applied to an inline pallet module",
+        ));
+    };
 
     // add storage
-    let storage = quote!(
-        /// A migration can span across multiple blocks. This storage defines a cursor to track the
-        /// progress of the migration, enabling us to resume from the last completed position.
+    let storage = if args.state {
+        quote!(
+            /// The state of the migrations. While in progress, it holds a cursor to track the
+            /// progress of the migration across blocks, enabling us to resume from the last
+            /// completed position.
+            #[pallet::storage]
+            pub type MigrationState<T: Config> = StorageValue<
+                _,
+                migratable::MigrationState,
+                frame_support::storage::types::ValueQuery,
+            >;
+        )
+    } else {
+        quote!(
+            /// A migration can span across multiple blocks. This storage defines a cursor to track
+            /// the progress of the migration, enabling us to resume from the last completed
+            /// position.
+            #[pallet::storage]
+            pub type MigrationInProgress<T: Config> =
+                StorageValue<_, migratable::Cursor, frame_support::storage::types::OptionQuery>;
+        )
+    };
+    content.push(parse_quote! { #storage });
+    let weight_consumed = quote!(
+        /// The weight consumed so far by the migration in progress, accumulated across blocks.
+        /// It is cleared along with `MigrationInProgress` once all migrations are completed.
         #[pallet::storage]
-        pub type MigrationInProgress<T: Config> =
-            StorageValue<_, migratable::Cursor, frame_support::storage::types::OptionQuery>;
+        pub type MigrationWeightConsumed<T: Config> = StorageValue<
+            _,
+            frame_support::weights::Weight,
+            frame_support::storage::types::ValueQuery,
+        >;
     );
-    content.push(parse_quote! { #storage });
+    content.push(parse_quote! { #weight_consumed });
+    let steps_done = quote!(
+        /// The number of steps run so far by the version in progress, accumulated across blocks.
+        /// It is cleared once the version is completed.
+        ///
+        /// Unlike the `u32` steps of a single call, it is a `u64`, which fine-grained migrations
+        /// running over millions of blocks can't saturate.
+        /// Counters stored as a `u32` by earlier releases are widened by
+        /// `migratable::MigrateStepsDoneToU64`.
+        #[pallet::storage]
+        pub type MigrationStepsDone<T: Config> =
+            StorageValue<_, u64, frame_support::storage::types::ValueQuery>;
+    );
+    content.push(parse_quote! { #steps_done });
+    let step_failures = quote!(
+        /// The number of transactional steps of the version in progress that failed in a row. The
+        /// migration is halted once it reaches `Config::MAX_STEP_FAILURES`.
+        #[pallet::storage]
+        pub type MigrationStepFailures<T: Config> =
+            StorageValue<_, u32, frame_support::storage::types::ValueQuery>;
+    );
+    content.push(parse_quote! { #step_failures });
+    let steps_in_block = quote!(
+        /// The block of the last steps of the version in progress, with the number of steps run in
+        /// it, counted against `MigrationStep::max_steps_per_block` across `migrate` calls. It is
+        /// cleared once the version is completed.
+        #[pallet::storage]
+        pub type MigrationStepsInBlock<T: Config> = StorageValue<
+            _,
+            (frame_system::pallet_prelude::BlockNumberFor<T>, u32),
+            frame_support::storage::types::OptionQuery,
+        >;
+    );
+    content.push(parse_quote! { #steps_in_block });
+    let started_at = quote!(
+        /// The block the version in progress started at, i.e. the block of its first step.
+        #[pallet::storage]
+        pub type MigrationStartedAt<T: Config> = StorageValue<
+            _,
+            frame_system::pallet_prelude::BlockNumberFor<T>,
+            frame_support::storage::types::OptionQuery,
+        >;
+    );
+    content.push(parse_quote! { #started_at });
+    let durations = quote!(
+        /// The number of blocks each completed version took, from its first step to its completion,
+        /// keyed by the version number.
+        #[pallet::storage]
+        pub type MigrationDurations<T: Config> = StorageMap<
+            _,
+            frame_support::Twox64Concat,
+            u16,
+            frame_system::pallet_prelude::BlockNumberFor<T>,
+            frame_support::storage::types::OptionQuery,
+        >;
+    );
+    content.push(parse_quote! { #durations });
+    let start_block = quote!(
+        /// The block before which the migrations don't run, even if a migration is in progress.
+        /// It is cleared once reached.
+        #[pallet::storage]
+        pub type MigrationStartBlock<T: Config> = StorageValue<
+            _,
+            frame_system::pallet_prelude::BlockNumberFor<T>,
+            frame_support::storage::types::OptionQuery,
+        >;
+    );
+    content.push(parse_quote! { #start_block });
+    let started_in_upgrade = quote!(
+        /// The block of the runtime upgrade that started the migration in progress, as seen by
+        /// `on_runtime_upgrade`. Cleared by the first call to `migrate` that follows.
+        #[pallet::storage]
+        pub type MigrationStartedInUpgrade<T: Config> = StorageValue<
+            _,
+            frame_system::pallet_prelude::BlockNumberFor<T>,
+            frame_support::storage::types::OptionQuery,
+        >;
+    );
+    content.push(parse_quote! { #started_in_upgrade });
 
     // add migration struct
     let migration = quote!(
@@ -170,24 +780,294 @@ pub fn pallet(
         /// If `TEST_ALL_STEPS == true` and `try-runtime` is enabled, this will run all the migrations
         /// inside `on_runtime_upgrade`. This should be set to false in tests that want to ensure the step
         /// by step migratable works.
-        pub struct Migration<T: Config, const TEST_ALL_STEPS: bool = true>(
+        pub struct #migration_ident<T: Config, const TEST_ALL_STEPS: bool = true>(
             core::marker::PhantomData<T>,
         );
     );
     content.push(parse_quote! { #migration });
 
+    // add view functions
+    if args.view_functions {
+        let view_functions = quote!(
+            #[pallet::view_functions]
+            impl<T: Config> Pallet<T> {
+                /// Returns the status of the migrations of this pallet.
+                pub fn migration_status() -> migratable::MigrationStatus {
+                    #migration_ident::<T>::status()
+                }
+            }
+        );
+        content.push(parse_quote! { #view_functions });
+    }
+
+    // add the weights to the pallet constants
+    if args.constants {
+        add_to_pallet_impl(content, "extra_constants", generate_constants());
+    }
+
+    // add dispatchables
+    if args.calls {
+        add_calls(content, generate_calls(migration_ident))?;
+    }
+
+    // add migration events
+    let event = if args.events {
+        Some(add_events(content, &input.ident)?)
+    } else {
+        None
+    };
+
     // add migration logic
-    let expand = generate_mod_expand();
+    let expand = generate_mod_expand(args, event);
     content.push(parse_quote! { #expand });
 
-    let output = quote! {
+    Ok(quote! {
         #input
-    };
-    output.into()
+    })
+}
+
+/// Generates the pallet constants exposing the weights of the migrations in the metadata.
+///
+/// The weights are computed by functions, so they are exposed through `#[pallet::extra_constants]`,
+/// which are evaluated when the metadata is built.
+fn generate_constants() -> proc_macro2::TokenStream {
+    quote!(
+        /// The weight of the generated `migrate` call, on top of the weight of the migration steps
+        /// it runs.
+        pub fn migrate_weight() -> frame_support::weights::Weight {
+            <migratable::weights::SubstrateWeight<T> as migratable::weights::WeightInfo>::migrate()
+        }
+
+        /// The weight of the generated `migrate` call when no migration is in progress.
+        pub fn migration_noop_weight() -> frame_support::weights::Weight {
+            <migratable::weights::SubstrateWeight<T> as migratable::weights::WeightInfo>::migration_noop()
+        }
+
+        /// The maximum weight of migration steps a single `migrate` call can run, see
+        /// `Config::max_migrate_call_weight`.
+        pub fn max_migrate_call_weight() -> frame_support::weights::Weight {
+            T::max_migrate_call_weight()
+        }
+    )
+}
+
+/// Generates the migration dispatchables, which delegate to the `Migration` struct.
+fn generate_calls(migration_ident: &syn::Ident) -> proc_macro2::TokenStream {
+    let migrate_call_index = proc_macro2::Literal::u8_unsuffixed(MIGRATE_CALL_INDEX);
+    quote!(
+        /// Sets the on-chain storage version of the pallet, without running the migrations in
+        /// between.
+        ///
+        /// Meant for chains started from a snapshot whose storage is already at `version`. Fails if
+        /// a migration is in progress.
+        #[pallet::call_index(255)]
+        #[pallet::weight(
+            <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get()
+                .reads_writes(1, 1)
+        )]
+        pub fn force_set_storage_version(
+            origin: frame_system::pallet_prelude::OriginFor<T>,
+            version: u16,
+        ) -> frame_support::dispatch::DispatchResult {
+            frame_system::ensure_root(origin)?;
+            #migration_ident::<T>::force_set_storage_version(version)
+        }
+
+        /// Runs the migration in progress with up to `weight_limit`, capped at
+        /// `Config::max_migrate_call_weight`, refunding the weight that is not used.
+        ///
+        /// The fee is paid whatever the outcome, so that the call can't be spammed for free.
+        #[pallet::call_index(#migrate_call_index)]
+        #[pallet::weight(
+            <migratable::weights::SubstrateWeight<T> as migratable::weights::WeightInfo>::migrate()
+                .saturating_add(weight_limit.min(T::max_migrate_call_weight()))
+        )]
+        pub fn migrate(
+            origin: frame_system::pallet_prelude::OriginFor<T>,
+            weight_limit: frame_support::weights::Weight,
+        ) -> frame_support::dispatch::DispatchResultWithPostInfo {
+            frame_system::ensure_signed(origin)?;
+            let weight_limit = weight_limit.min(T::max_migrate_call_weight());
+            let (_, weight) = #migration_ident::<T>::migrate(weight_limit);
+            Ok(Some(weight).into())
+        }
+
+        /// Checks the invariants of an already migrated `version`, see `MigrationStep::verify`.
+        ///
+        /// A failure is reported by the `MigrateError::VerificationFailed` error of the call, and
+        /// a success by the `MigrationVerified` event if the `events` argument is given. The checks
+        /// must fit into `Config::max_migrate_call_weight`.
+        #[pallet::call_index(253)]
+        #[pallet::weight(T::max_migrate_call_weight())]
+        pub fn verify_migration(
+            origin: frame_system::pallet_prelude::OriginFor<T>,
+            version: u16,
+        ) -> frame_support::dispatch::DispatchResult {
+            frame_system::ensure_root(origin)?;
+            #migration_ident::<T>::verify(version).map_err(Into::into)
+        }
+
+        /// Schedules the migrations to start at `block`, even if the runtime upgrade already
+        /// happened. `None` lets them run right away.
+        #[pallet::call_index(252)]
+        #[pallet::weight(
+            <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get()
+                .writes(1)
+        )]
+        pub fn set_migration_start_block(
+            origin: frame_system::pallet_prelude::OriginFor<T>,
+            block: Option<frame_system::pallet_prelude::BlockNumberFor<T>>,
+        ) -> frame_support::dispatch::DispatchResult {
+            frame_system::ensure_root(origin)?;
+            #migration_ident::<T>::set_start_block(block);
+            Ok(())
+        }
+
+        /// Replaces the cursor of the migration in progress, e.g. to resume it from a known-good
+        /// cursor backed up before an incident.
+        ///
+        /// It also resumes a migration halted after `Config::MAX_STEP_FAILURES` failed steps. Fails
+        /// if no migration is in progress, or if `cursor` doesn't decode as the step of the version
+        /// in progress.
+        #[pallet::call_index(251)]
+        #[pallet::weight(
+            <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get()
+                .reads_writes(2, 2)
+        )]
+        pub fn force_set_cursor(
+            origin: frame_system::pallet_prelude::OriginFor<T>,
+            cursor: migratable::Cursor,
+        ) -> frame_support::dispatch::DispatchResult {
+            frame_system::ensure_root(origin)?;
+            #migration_ident::<T>::force_set_cursor(cursor)
+        }
+
+        /// Removes up to `limit` entries of `MigrationDurations`, refunding the weight of the
+        /// entries left, so that old records can be pruned in bounded calls.
+        ///
+        /// The call can be repeated in later blocks until no entry is left, resuming from the
+        /// `maybe_cursor` of the previous call. The number of entries removed and the cursor are
+        /// reported by the `MigrationHistoryCleared` event if the `events` argument is given, and
+        /// logged otherwise.
+        #[pallet::call_index(250)]
+        #[pallet::weight(
+            <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get()
+                .reads_writes((*limit).into(), (*limit).into())
+        )]
+        pub fn clear_migration_history(
+            origin: frame_system::pallet_prelude::OriginFor<T>,
+            limit: u32,
+            maybe_cursor: Option<frame_support::sp_std::vec::Vec<u8>>,
+        ) -> frame_support::dispatch::DispatchResultWithPostInfo {
+            frame_system::ensure_root(origin)?;
+            let (_, _, weight) =
+                #migration_ident::<T>::clear_history(limit, maybe_cursor.as_deref());
+            Ok(Some(weight).into())
+        }
+    )
+}
+
+/// Generates the accessors of the migration cursor, abstracting over the storage it is kept in.
+fn generate_cursor_accessors(state: bool) -> proc_macro2::TokenStream {
+    if state {
+        quote!(
+            pub(crate) fn in_progress() -> bool {
+                matches!(MigrationState::<T>::get(), migratable::MigrationState::InProgress(_))
+            }
+
+            fn cursor() -> Option<migratable::Cursor> {
+                match MigrationState::<T>::get() {
+                    migratable::MigrationState::InProgress(cursor) => Some(cursor),
+                    _ => None,
+                }
+            }
+
+            fn set_cursor(cursor: Option<migratable::Cursor>) {
+                MigrationState::<T>::put(match cursor {
+                    Some(cursor) => migratable::MigrationState::InProgress(cursor),
+                    None => migratable::MigrationState::Completed,
+                });
+            }
+
+            fn mutate_cursor<R>(f: impl FnOnce(&mut Option<migratable::Cursor>) -> R) -> R {
+                MigrationState::<T>::mutate_exists(|state| {
+                    let mut cursor = match state {
+                        Some(migratable::MigrationState::InProgress(cursor)) => {
+                            Some(cursor.clone())
+                        }
+                        _ => None,
+                    };
+                    let result = f(&mut cursor);
+                    match cursor {
+                        Some(cursor) => {
+                            *state = Some(migratable::MigrationState::InProgress(cursor))
+                        }
+                        // a pending state is left untouched
+                        None if matches!(
+                            state,
+                            Some(migratable::MigrationState::InProgress(_))
+                        ) =>
+                        {
+                            *state = Some(migratable::MigrationState::Completed)
+                        }
+                        None => {}
+                    }
+                    result
+                })
+            }
+        )
+    } else {
+        quote!(
+            pub(crate) fn in_progress() -> bool {
+                MigrationInProgress::<T>::exists()
+            }
+
+            fn cursor() -> Option<migratable::Cursor> {
+                MigrationInProgress::<T>::get()
+            }
+
+            fn set_cursor(cursor: Option<migratable::Cursor>) {
+                MigrationInProgress::<T>::set(cursor);
+            }
+
+            fn mutate_cursor<R>(f: impl FnOnce(&mut Option<migratable::Cursor>) -> R) -> R {
+                MigrationInProgress::<T>::mutate_exists(f)
+            }
+        )
+    }
 }
 
 /// Implements the required traits for the `Migration` struct.
-fn generate_mod_expand() -> proc_macro2::TokenStream {
+fn generate_mod_expand(
+    args: &PalletArgs,
+    event: Option<proc_macro2::TokenStream>,
+) -> proc_macro2::TokenStream {
+    let migration_ident = &args.migration_ident;
+    let cursor_accessors = generate_cursor_accessors(args.state);
+    let deposit_event = event.as_ref().map(|event| {
+        quote!(
+            fn deposit_migration_event(event: #event) {
+                let event = <<T as Config>::RuntimeEvent as From<#event>>::from(event);
+                let event = <<T as Config>::RuntimeEvent as Into<
+                    <T as frame_system::Config>::RuntimeEvent,
+                >>::into(event);
+                <frame_system::Pallet<T>>::deposit_event(event);
+            }
+        )
+    });
+    let verified_event = event.as_ref().map(|event| {
+        quote!(
+            Self::deposit_migration_event(#event::MigrationVerified { version });
+        )
+    });
+    let history_cleared_event = event.as_ref().map(|event| {
+        quote!(
+            Self::deposit_migration_event(#event::MigrationHistoryCleared {
+                removed: result.unique,
+                maybe_cursor: result.maybe_cursor.clone(),
+            });
+        )
+    });
     quote!(
         const _: () = {
             use migratable::MigrateSequence;
@@ -195,29 +1075,84 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
 
             const _: () = {
                 #[cfg(feature = "try-runtime")]
-                impl<T: Config, const TEST_ALL_STEPS: bool> Migration<T, TEST_ALL_STEPS> {
+                impl<T: Config, const TEST_ALL_STEPS: bool> #migration_ident<T, TEST_ALL_STEPS> {
                     fn run_all_steps() -> Result<(), sp_runtime::TryRuntimeError> {
                         let mut weight = frame_support::weights::Weight::zero();
                         let name = <Pallet<T>>::name();
+                        let max_block_weight =
+                            <T as frame_system::Config>::BlockWeights::get().max_block;
                         loop {
-                            let in_progress_version = <Pallet<T>>::on_chain_storage_version() + 1;
-                            let state = T::Migrations::pre_upgrade_step(in_progress_version)?;
-                            let (status, w) = Self::migrate(frame_support::weights::Weight::MAX);
-                            weight.saturating_accrue(w);
+                            let in_progress_version =
+                                Self::in_progress_version().ok_or("No migration in progress")?;
+                            if T::Migrations::skip_in_test_all(in_progress_version) {
+                                migratable::log::info!(
+                                    target: LOG_TARGET,
+                                    "{name}: Skipping migration step {:?}",
+                                    in_progress_version
+                                );
+                                in_progress_version.put::<Pallet<T>>();
+                                MigrationStepsDone::<T>::kill();
+                                if let Some(next_version) = Self::next_version_to_migrate(in_progress_version) {
+                                    Self::set_cursor(Some(T::Migrations::new(next_version)));
+                                    continue;
+                                }
+                                Self::set_cursor(None);
+                                MigrationWeightConsumed::<T>::kill();
+                                T::on_migrations_completed();
+                                break;
+                            }
+                            let (state, pre_weight) =
+                                T::Migrations::pre_upgrade_step(in_progress_version)?;
+                            // the version may take several calls if its steps yield
+                            let status = loop {
+                                let (status, w) =
+                                    Self::migrate(frame_support::weights::Weight::MAX);
+                                weight.saturating_accrue(w);
+                                match status {
+                                    migratable::MigrateResult::Failed { reason } => {
+                                        return Err(reason.into())
+                                    }
+                                    migratable::MigrateResult::InProgress { steps_done }
+                                    | migratable::MigrateResult::Yielded { steps_done }
+                                        if Self::in_progress_version()
+                                            == Some(in_progress_version) =>
+                                    {
+                                        if steps_done == 0 {
+                                            return Err("Migration step made no progress".into());
+                                        }
+                                    }
+                                    status => break status,
+                                }
+                            };
                             migratable::log::info!(
                                 target: LOG_TARGET,
                                 "{name}: Migration step {:?} weight = {}",
                                 in_progress_version,
-                                weight
+                                migratable::LogWeight(weight)
                             );
-                            T::Migrations::post_upgrade_step(in_progress_version, state)?;
-                            if matches!(status, MigrateResult::Completed) {
+                            let post_weight =
+                                T::Migrations::post_upgrade_step(in_progress_version, state)?;
+                            let checks_weight = pre_weight.saturating_add(post_weight);
+                            weight.saturating_accrue(checks_weight);
+                            if checks_weight.any_gt(max_block_weight) {
+                                migratable::log::warn!(
+                                    target: LOG_TARGET,
+                                    "{name}: Checks of migration step {:?} weigh {}, more than a block ({})",
+                                    in_progress_version,
+                                    migratable::LogWeight(checks_weight),
+                                    migratable::LogWeight(max_block_weight)
+                                );
+                            }
+                            if matches!(status, migratable::MigrateResult::Completed) {
                                 break;
                             }
                         }
 
-                        let name = <Pallet<T>>::name();
-                        log::info!(target: LOG_TARGET, "{name}: Migration steps weight = {}", weight);
+                        migratable::log::info!(
+                            target: LOG_TARGET,
+                            "{name}: Migration steps weight (including checks) = {}",
+                            migratable::LogWeight(weight)
+                        );
                         Ok(())
                     }
                 }
@@ -226,18 +1161,31 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
             const _: () = {
                 use migratable::weights::WeightInfo;
                 impl<T: Config, const TEST_ALL_STEPS: bool> frame_support::traits::OnRuntimeUpgrade
-                    for Migration<T, TEST_ALL_STEPS>
+                    for #migration_ident<T, TEST_ALL_STEPS>
                 {
                     fn on_runtime_upgrade() -> frame_support::weights::Weight {
                         let name = <Pallet<T>>::name();
-                        let latest_version = <Pallet<T>>::current_storage_version();
+                        let latest_version = Self::target_version();
                         let storage_version = <Pallet<T>>::on_chain_storage_version();
 
-                        if storage_version == latest_version {
+                        // The runtime was downgraded to a version older than the storage.
+                        let current_version = <Pallet<T>>::current_storage_version();
+                        if storage_version > current_version {
+                            migratable::log::error!(
+                                target: LOG_TARGET,
+                                "{name}: On-chain storage version {:?} is ahead of the pallet's storage version {:?}. Was the runtime downgraded?",
+                                &storage_version,
+                                &current_version
+                            );
+                            return migratable::weights::SubstrateWeight::<T>::on_runtime_upgrade_noop();
+                        }
+
+                        if storage_version >= latest_version {
                             migratable::log::warn!(
                                 target: LOG_TARGET,
-                                "{name}: No Migration performed storage_version = latest_version = {:?}",
-                                &storage_version
+                                "{name}: No Migration performed storage_version = {:?}, latest_version = {:?}",
+                                &storage_version,
+                                &latest_version
                             );
                             return migratable::weights::SubstrateWeight::<T>::on_runtime_upgrade_noop();
                         }
@@ -245,22 +1193,61 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
                         // In case a migratable is already in progress we create the next migratable
                         // (if any) right when the current one finishes.
                         if Self::in_progress() {
-                            migratable::log::warn!(
-                                target: LOG_TARGET,
-                                "{name}: Migration already in progress {:?}",
-                                &storage_version
-                            );
+                            // the block number isn't set yet for the upgrade block, but every
+                            // call of the same upgrade sees the same one
+                            let now = <frame_system::Pallet<T>>::block_number();
+                            if MigrationStartedInUpgrade::<T>::get() == Some(now) {
+                                // started by a previous call within the same upgrade, e.g. if the
+                                // migration is listed twice
+                                migratable::log::debug!(
+                                    target: LOG_TARGET,
+                                    "{name}: Migration already started in this upgrade {:?}",
+                                    &storage_version
+                                );
+                            } else {
+                                migratable::log::warn!(
+                                    target: LOG_TARGET,
+                                    "{name}: Migration already in progress {:?}",
+                                    &storage_version
+                                );
+                            }
 
                             return migratable::weights::SubstrateWeight::<T>::on_runtime_upgrade_in_progress();
                         }
 
+                        // The sequence doesn't start right after the on-chain version, e.g. its
+                        // older versions were pruned too early. Nothing is started, rather than
+                        // failing every upgrade block.
+                        if !T::Migrations::is_upgrade_supported(storage_version, latest_version) {
+                            migratable::log::error!(
+                                target: LOG_TARGET,
+                                "{name}: Unsupported upgrade from {storage_version:?} to {latest_version:?}, migrations cover {:?}",
+                                T::Migrations::VERSION_RANGE,
+                            );
+                            return migratable::weights::SubstrateWeight::<T>::on_runtime_upgrade_noop();
+                        }
+
                         migratable::log::info!(
                             target: LOG_TARGET,
                             "{name}: Upgrading storage from {storage_version:?} to {latest_version:?}.",
                         );
 
-                        let cursor = T::Migrations::new(storage_version + 1);
-                        MigrationInProgress::<T>::set(Some(cursor));
+                        // `storage_version` is below `latest_version`, so it can't overflow
+                        let Some(next_version) = migratable::next_version(storage_version) else {
+                            return migratable::weights::SubstrateWeight::<T>::on_runtime_upgrade_noop();
+                        };
+                        let (cursor, start_weight) = match T::Migrations::start(next_version) {
+                            Ok(started) => started,
+                            Err(error) => {
+                                migratable::log::error!(
+                                    target: LOG_TARGET,
+                                    "{name}: Failed to start the migration of {next_version:?}: {error:?}",
+                                );
+                                return migratable::weights::SubstrateWeight::<T>::on_runtime_upgrade_noop();
+                            }
+                        };
+                        Self::set_cursor(Some(cursor));
+                        MigrationStartedInUpgrade::<T>::put(<frame_system::Pallet<T>>::block_number());
 
                         #[cfg(feature = "try-runtime")]
                         if TEST_ALL_STEPS {
@@ -268,6 +1255,7 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
                         }
 
                         migratable::weights::SubstrateWeight::<T>::on_runtime_upgrade()
+                            .saturating_add(start_weight)
                     }
 
                     #[cfg(feature = "try-runtime")]
@@ -278,12 +1266,12 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
                         // Instead, we call the migrations `pre_upgrade` and `post_upgrade` hooks when we iterate
                         // over our migrations.
                         let storage_version = <Pallet<T>>::on_chain_storage_version();
-                        let target_version = <Pallet<T>>::current_storage_version();
+                        let target_version = Self::target_version();
 
                         ensure!(
-								storage_version != target_version,
-								"No upgrade: Please remove this migratable from your runtime upgrade configuration."
-							);
+                                storage_version != target_version,
+                                "No upgrade: Please remove this migratable from your runtime upgrade configuration."
+                            );
 
                         migratable::log::debug!(
                             target: LOG_TARGET,
@@ -292,22 +1280,230 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
                         );
 
                         ensure!(
-								T::Migrations::is_upgrade_supported(storage_version, target_version),
-								"Unsupported upgrade: VERSION_RANGE should be (on-chain storage version + 1, current storage version)"
-							);
+                                T::Migrations::is_upgrade_supported(storage_version, target_version),
+                                "Unsupported upgrade: VERSION_RANGE should contain on-chain storage version + 1 and reach the target version"
+                            );
+
+                        // A migration carried across the upgrade must still resume with the new code.
+                        if let Some(cursor) = Self::cursor() {
+                            let version = Self::in_progress_version()
+                                .ok_or("Migration in progress, but no version left to migrate")?;
+                            ensure!(
+                                T::Migrations::validate_cursor(version, &cursor),
+                                "Migration in progress, but its cursor doesn't decode for the version in progress"
+                            );
+                        }
                         Ok(Default::default())
                     }
                 }
             };
 
+            const _: () = {
+                impl<T: Config, const TEST_ALL_STEPS: bool> migratable::PalletMigration
+                    for #migration_ident<T, TEST_ALL_STEPS>
+                {
+                    fn pallet_name() -> &'static str {
+                        <Pallet<T> as frame_support::traits::PalletInfoAccess>::name()
+                    }
+
+                    fn status() -> migratable::MigrationStatus {
+                        Self::status()
+                    }
+
+                    fn can_start() -> bool {
+                        <T::Migrations as migratable::MigrateSequence>::is_upgrade_supported(
+                            <Pallet<T>>::on_chain_storage_version(),
+                            Self::target_version(),
+                        )
+                    }
+
+                    fn start_weight() -> frame_support::weights::Weight {
+                        <migratable::weights::SubstrateWeight<T> as migratable::weights::WeightInfo>::on_runtime_upgrade()
+                    }
+
+                    fn migrate_idle(
+                        remaining_weight: &mut frame_support::weights::Weight,
+                    ) -> frame_support::weights::Weight {
+                        Self::migrate_idle(remaining_weight)
+                    }
+
+                    fn integrity_check() -> frame_support::sp_std::vec::Vec<migratable::IntegrityError> {
+                        Self::integrity_check()
+                    }
+                }
+            };
+
+            // only with the `test-utils` feature of `migratable`
+            migratable::__test_utils! {
+                impl<T: Config, const TEST_ALL_STEPS: bool> migratable::testing::PalletMigrationTestUtils
+                    for #migration_ident<T, TEST_ALL_STEPS>
+                {
+                    fn migrate(
+                        weight_limit: frame_support::weights::Weight,
+                    ) -> (migratable::MigrateResult, frame_support::weights::Weight) {
+                        Self::migrate(weight_limit)
+                    }
+
+                    fn rewind(to: frame_support::traits::StorageVersion) {
+                        to.put::<Pallet<T>>();
+                        Self::set_cursor(
+                            Self::next_version_to_migrate(to)
+                                .map(<T::Migrations as migratable::MigrateSequence>::new),
+                        );
+                        MigrationStepsDone::<T>::kill();
+                        MigrationStepFailures::<T>::kill();
+                        MigrationStepsInBlock::<T>::kill();
+                        MigrationWeightConsumed::<T>::kill();
+                        MigrationStartedAt::<T>::kill();
+                        MigrationStartedInUpgrade::<T>::kill();
+                    }
+                }
+            }
+
             const _: () = {
                 use migratable::weights::WeightInfo;
-                impl<T: Config, const TEST_ALL_STEPS: bool> Migration<T, TEST_ALL_STEPS> {
+                impl<T: Config, const TEST_ALL_STEPS: bool> #migration_ident<T, TEST_ALL_STEPS> {
                     /// Verify that each migratable's step of the [`Config::Migrations`] sequence fits into
-                    /// `Cursor`.
+                    /// `Cursor`, panicking with every failed check.
                     pub(crate) fn integrity_test() {
+                        migratable::assert_integrity(Self::integrity_check());
+                    }
+
+                    /// Returns the failed checks of [`Self::integrity_test`].
+                    fn integrity_check() -> frame_support::sp_std::vec::Vec<migratable::IntegrityError> {
                         let max_weight = <T as frame_system::Config>::BlockWeights::get().max_block;
-                        T::Migrations::integrity_test(max_weight)
+                        let mut errors =
+                            T::Migrations::integrity_check(max_weight, migratable::Cursor::bound());
+                        // Unless deliberately capped, the pallet should be at the last version of
+                        // its migrations. Otherwise, migrations stop at the end of the sequence.
+                        let current = <Pallet<T>>::current_storage_version();
+                        let last = T::Migrations::last_version();
+                        if T::TARGET_VERSION.is_none()
+                            && T::Migrations::VERSION_RANGE != (0, 0)
+                            && current != last
+                        {
+                            errors.push(migratable::IntegrityError::StorageVersionMismatch {
+                                current: migratable::storage_version_number(current),
+                                last: migratable::storage_version_number(last),
+                            });
+                        }
+                        errors
+                    }
+
+                    /// Verify the consistency of the migration storage with the on-chain storage
+                    /// version, e.g. that neither steps, failed steps nor weight are accounted for
+                    /// while no migration is in progress.
+                    #[cfg(feature = "try-runtime")]
+                    pub(crate) fn try_state() -> Result<(), sp_runtime::TryRuntimeError> {
+                        let storage_version = <Pallet<T>>::on_chain_storage_version();
+                        let target_version = Self::target_version();
+                        match Self::cursor() {
+                            Some(cursor) => {
+                                let in_progress_version = migratable::next_version(storage_version)
+                                    .ok_or("A migration is in progress past the last storage version")?;
+                                frame_support::ensure!(
+                                    in_progress_version <= target_version,
+                                    "A migration is in progress past the target version"
+                                );
+                                frame_support::ensure!(
+                                    T::Migrations::describe_position(in_progress_version, &cursor).is_ok(),
+                                    "The migration cursor can't be decoded for the version in progress"
+                                );
+                            }
+                            None => {
+                                // Unsupported upgrades are left at their storage version, and a
+                                // migration may wait to be started on demand, through
+                                // `Config::on_no_migration`, or at its scheduled start block.
+                                let start_pending = T::on_no_migration().is_some_and(|version| {
+                                    Self::next_version_to_migrate(storage_version) == Some(version)
+                                }) || MigrationStartBlock::<T>::exists();
+                                frame_support::ensure!(
+                                    storage_version >= target_version
+                                        || start_pending
+                                        || !T::Migrations::is_upgrade_supported(storage_version, target_version),
+                                    "No migration is in progress, but the storage version is behind the target version"
+                                );
+                                frame_support::ensure!(
+                                    MigrationStepsDone::<T>::get() == 0,
+                                    "No migration is in progress, but steps are accounted for it"
+                                );
+                                frame_support::ensure!(
+                                    !MigrationStepFailures::<T>::exists(),
+                                    "No migration is in progress, but failed steps are accounted for it"
+                                );
+                                frame_support::ensure!(
+                                    !MigrationStepsInBlock::<T>::exists(),
+                                    "No migration is in progress, but steps are accounted for this block"
+                                );
+                                frame_support::ensure!(
+                                    MigrationWeightConsumed::<T>::get().is_zero(),
+                                    "No migration is in progress, but weight is accounted for it"
+                                );
+                                frame_support::ensure!(
+                                    !MigrationStartedAt::<T>::exists(),
+                                    "No migration is in progress, but a start block is recorded for it"
+                                );
+                            }
+                        }
+                        Ok(())
+                    }
+
+                    /// Runs the migrations with up to `remaining_weight`, as done in `on_idle`,
+                    /// reducing it by the weight they consumed.
+                    ///
+                    /// The consumed weight is returned as accumulated from what `migrate` reports
+                    /// instead of being derived from `remaining_weight`, which saturates at zero and
+                    /// would under-report an overrun.
+                    pub(crate) fn migrate_idle(
+                        remaining_weight: &mut frame_support::weights::Weight,
+                    ) -> frame_support::weights::Weight {
+                        let mut migration_weight =
+                            <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get().reads(1);
+                        remaining_weight.saturating_reduce(migration_weight);
+                        // Fast path: once all migrations are done, a single existence check is
+                        // all it takes, without the overhead of `migrate`.
+                        if !Self::in_progress() && T::on_no_migration().is_none() {
+                            return migration_weight;
+                        }
+                        // Not even the overhead of `migrate` fits, so it can't make any progress, or
+                        // the remaining weight is below the configured threshold.
+                        let min_weight =
+                            T::min_idle_weight_for_migration().max(Self::migrate_overhead());
+                        loop {
+                            if remaining_weight.any_lt(min_weight) {
+                                break;
+                            }
+                            // already read by `migrate`
+                            let version_before = <Pallet<T>>::on_chain_storage_version();
+                            let (result, weight) = Self::migrate(*remaining_weight);
+                            migration_weight.saturating_accrue(weight);
+                            remaining_weight.saturating_reduce(weight);
+
+                            match result {
+                                // There is not enough weight to perform a migration, or make any
+                                // progress, we stop here and leave the remaining weight to the rest
+                                // of the hook. Completing a skipped version takes no step, but it
+                                // is progress.
+                                migratable::MigrateResult::NoMigrationPerformed => break,
+                                migratable::MigrateResult::InProgress { steps_done: 0 }
+                                    if <Pallet<T>>::on_chain_storage_version() == version_before =>
+                                {
+                                    break
+                                }
+                                // Migration is still in progress, we can start the next step.
+                                migratable::MigrateResult::InProgress { .. } => continue,
+                                // The migration reached its cap of steps for this block.
+                                migratable::MigrateResult::Yielded { .. } => break,
+                                // The migration can't proceed in this block.
+                                migratable::MigrateResult::Failed { .. } => break,
+                                // Either no migration is in progress, or we are done with all
+                                // migrations, we can do some more other work with the remaining
+                                // weight.
+                                migratable::MigrateResult::Completed
+                                | migratable::MigrateResult::NoMigrationInProgress => break,
+                            }
+                        }
+                        migration_weight
                     }
 
                     /// Migrate
@@ -317,19 +1513,51 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
                     ) -> (migratable::MigrateResult, frame_support::weights::Weight)
                     {
                         let name = <Pallet<T>>::name();
+                        // shared with the migrations of the other pallets
+                        let weight_limit =
+                            migratable::budget::MigrationWeightBudget::<T>::clip(weight_limit);
                         let mut weight_left = weight_limit;
 
-                        if weight_left
-                            .checked_reduce(migratable::weights::SubstrateWeight::<T>::migrate())
-                            .is_none()
-                        {
+                        if weight_left.checked_reduce(Self::migrate_overhead()).is_none() {
                             return (
                                 migratable::MigrateResult::NoMigrationPerformed,
-                                Weight::zero(),
+                                frame_support::weights::Weight::zero(),
                             );
                         }
 
-                        MigrationInProgress::<T>::mutate_exists(|progress| {
+                        // the runtime-wide kill switch
+                        if !T::migrations_enabled() {
+                            migratable::log::warn!(
+                                target: LOG_TARGET,
+                                "{name}: Migrations are disabled",
+                            );
+                            return (
+                                migratable::MigrateResult::NoMigrationPerformed,
+                                <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get().reads(1),
+                            );
+                        }
+
+                        // the migrations don't run before the scheduled block, if any
+                        if let Some(start_block) = MigrationStartBlock::<T>::get() {
+                            if <frame_system::Pallet<T>>::block_number() < start_block {
+                                return (
+                                    migratable::MigrateResult::NoMigrationPerformed,
+                                    <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get().reads(2),
+                                );
+                            }
+                            MigrationStartBlock::<T>::kill();
+                        }
+
+                        Self::mutate_cursor(|progress| {
+                            // a migration started on demand
+                            let mut start_weight = frame_support::weights::Weight::zero();
+                            if progress.is_none() {
+                                if let Some((cursor, weight)) = Self::start_on_demand() {
+                                    *progress = Some(cursor);
+                                    start_weight = weight;
+                                }
+                            }
+
                             let Some(cursor_before) = progress.as_mut() else {
                                 return (
                                     migratable::MigrateResult::NoMigrationInProgress,
@@ -337,9 +1565,25 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
                                 );
                             };
 
+                            MigrationStartedInUpgrade::<T>::kill();
+
                             // if a migratable is running it is always upgrading to the next version
                             let storage_version = <Pallet<T>>::on_chain_storage_version();
-                            let in_progress_version = storage_version + 1;
+                            let Some(in_progress_version) = migratable::next_version(storage_version) else {
+                                // kept, like an invalid cursor, rather than unlocking the calls
+                                migratable::log::error!(
+                                    target: LOG_TARGET,
+                                    "{name}: No version follows {:?}. Keeping the cursor.",
+                                    storage_version,
+                                );
+                                return (
+                                    migratable::MigrateResult::Failed {
+                                        reason: migratable::MigrateError::UnsupportedVersion(u16::MAX)
+                                            .as_str(),
+                                    },
+                                    migratable::weights::SubstrateWeight::<T>::migration_noop(),
+                                );
+                            };
 
                             migratable::log::info!(
                                 target: LOG_TARGET,
@@ -348,55 +1592,601 @@ fn generate_mod_expand() -> proc_macro2::TokenStream {
                                 in_progress_version,
                             );
 
-                            let result = match T::Migrations::steps(
+                            // a migration whose steps keep failing is halted until its cursor is
+                            // replaced
+                            if MigrationStepFailures::<T>::get() >= T::MAX_STEP_FAILURES {
+                                migratable::log::error!(
+                                    target: LOG_TARGET,
+                                    "{name}: Migration {:?} halted after {} failed steps",
+                                    in_progress_version,
+                                    T::MAX_STEP_FAILURES,
+                                );
+                                return (
+                                    migratable::MigrateResult::Failed {
+                                        reason: migratable::MigrateError::Stalled.as_str(),
+                                    },
+                                    {
+                                        let mut weight =
+                                            migratable::weights::SubstrateWeight::<T>::migration_noop();
+                                        migratable::add_weight(&mut weight, start_weight);
+                                        weight
+                                    },
+                                );
+                            }
+
+                            // The bookkeeping is already charged by `migrate_overhead`. The work
+                            // whose weight is only known once done is charged to `weight_left`
+                            // as well, and the part that doesn't fit is added on top.
+                            let mut extra_weight = frame_support::weights::Weight::zero();
+                            migratable::charge_weight(&mut weight_left, &mut extra_weight, start_weight);
+
+                            // dependencies are only checked before the first step of the version
+                            if MigrationStepsDone::<T>::get() == 0 {
+                                if let Err(reason) = T::Migrations::check_dependencies(in_progress_version) {
+                                    // The cursor is kept, so that the migration is retried once the
+                                    // dependencies are satisfied.
+                                    migratable::log::error!(
+                                        target: LOG_TARGET,
+                                        "{name}: Dependencies of {:?} are not satisfied: {}",
+                                        in_progress_version,
+                                        reason,
+                                    );
+                                    return (
+                                        migratable::MigrateResult::Failed { reason },
+                                        {
+                                            let mut weight =
+                                                migratable::consumed_weight(weight_limit, weight_left);
+                                            migratable::add_weight(&mut weight, extra_weight);
+                                            weight
+                                        },
+                                    );
+                                }
+                                if !MigrationStartedAt::<T>::exists() {
+                                    MigrationStartedAt::<T>::put(<frame_system::Pallet<T>>::block_number());
+                                }
+                            }
+                            // reserved, so that completing the version, and the no-op versions
+                            // right after, doesn't overrun the weight limit
+                            let advance_weight = migratable::version_advance_weight(
+                                <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get(),
+                            );
+                            let noops_weight =
+                                <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get().writes(1);
+                            // the last cursor checkpointed by the steps, which is only persisted if
+                            // the call fails, as the final cursor supersedes it otherwise
+                            let mut checkpointed = None;
+                            // the steps already run in this block, by previous `migrate` calls
+                            let now = <frame_system::Pallet<T>>::block_number();
+                            let steps_in_block = MigrationStepsInBlock::<T>::get()
+                                .filter(|(block, _)| *block == now)
+                                .map_or(0, |(_, steps)| steps);
+                            let steps = migratable::steps_with_reserve::<T::Migrations>(
                                 in_progress_version,
                                 cursor_before.as_ref(),
                                 &mut weight_left,
-                            ) {
+                                steps_in_block,
+                                advance_weight.saturating_add(noops_weight),
+                                &mut |cursor| checkpointed = Some(cursor),
+                            );
+                            let step_result = match steps {
+                                Ok(step_result) => step_result,
+                                Err(error @ migratable::MigrateError::CursorOverflow) => {
+                                    // The step outgrew the cursor. Its last checkpoint, or else its
+                                    // last persisted cursor, is kept, so that a runtime upgrade can
+                                    // fix the step.
+                                    frame_support::defensive!(
+                                        "Migration step doesn't fit into the cursor",
+                                        in_progress_version,
+                                    );
+                                    if let Some(cursor) = checkpointed {
+                                        *progress = Some(cursor);
+                                    }
+                                    return (
+                                        migratable::MigrateResult::Failed {
+                                            reason: error.as_str(),
+                                        },
+                                        migratable::consumed_weight(weight_limit, weight_left),
+                                    );
+                                }
+                                Err(error) => {
+                                    // The cursor doesn't belong to the version being migrated. It is
+                                    // kept, and the calls stay blocked, as the storage may be half
+                                    // migrated: the migration is halted until the cursor is replaced,
+                                    // e.g. through `force_set_cursor`, or a runtime upgrade fixes it.
+                                    migratable::log::error!(
+                                        target: LOG_TARGET,
+                                        "{name}: Invalid cursor for {:?}: {:?}. Keeping it.",
+                                        in_progress_version,
+                                        error,
+                                    );
+                                    if let Some(cursor) = checkpointed {
+                                        *progress = Some(cursor);
+                                    }
+                                    return (
+                                        migratable::MigrateResult::Failed {
+                                            reason: error.as_str(),
+                                        },
+                                        migratable::consumed_weight(weight_limit, weight_left),
+                                    );
+                                }
+                            };
+                            let steps_weight = migratable::consumed_weight(weight_limit, weight_left);
+                            let steps_done = step_result.steps_done();
+                            if matches!(step_result, migratable::StepResult::Completed { .. }) {
+                                MigrationStepsInBlock::<T>::kill();
+                            } else if steps_done > 0 {
+                                MigrationStepsInBlock::<T>::put((now, steps_in_block.saturating_add(steps_done)));
+                            }
+                            let result = match step_result {
                                 migratable::StepResult::InProgress { cursor, steps_done } => {
+                                    <T::MigrationObserver as migratable::MigrationObserver>::on_step(
+                                        in_progress_version,
+                                        steps_done,
+                                        steps_weight,
+                                    );
                                     *progress = Some(cursor);
+                                    MigrationStepsDone::<T>::mutate(|total| {
+                                        *total = migratable::accrue_steps(*total, steps_done)
+                                    });
+                                    if steps_done > 0 {
+                                        MigrationStepFailures::<T>::kill();
+                                    }
                                     migratable::MigrateResult::InProgress { steps_done }
                                 }
+                                migratable::StepResult::Yielded { cursor, steps_done } => {
+                                    <T::MigrationObserver as migratable::MigrationObserver>::on_step(
+                                        in_progress_version,
+                                        steps_done,
+                                        steps_weight,
+                                    );
+                                    *progress = Some(cursor);
+                                    MigrationStepsDone::<T>::mutate(|total| {
+                                        *total = migratable::accrue_steps(*total, steps_done)
+                                    });
+                                    if steps_done > 0 {
+                                        MigrationStepFailures::<T>::kill();
+                                    }
+                                    migratable::MigrateResult::Yielded { steps_done }
+                                }
+                                migratable::StepResult::Failed { cursor, steps_done } => {
+                                    migratable::log::error!(
+                                        target: LOG_TARGET,
+                                        "{name}: Migration {:?} failed after {} steps. Keeping the cursor.",
+                                        in_progress_version,
+                                        steps_done,
+                                    );
+                                    *progress = Some(cursor);
+                                    MigrationStepsDone::<T>::mutate(|total| {
+                                        *total = migratable::accrue_steps(*total, steps_done)
+                                    });
+                                    // only the steps failing in a row count
+                                    MigrationStepFailures::<T>::mutate(|failures| {
+                                        *failures = if steps_done > 0 {
+                                            1
+                                        } else {
+                                            failures.saturating_add(1)
+                                        }
+                                    });
+                                    migratable::MigrateResult::Failed {
+                                        reason: migratable::MigrateError::StepFailed.as_str(),
+                                    }
+                                }
                                 migratable::StepResult::Completed { steps_done } => {
+                                    <T::MigrationObserver as migratable::MigrationObserver>::on_step(
+                                        in_progress_version,
+                                        steps_done,
+                                        steps_weight,
+                                    );
                                     in_progress_version.put::<Pallet<T>>();
-                                    if <Pallet<T>>::current_storage_version() != in_progress_version
+                                    MigrationStepFailures::<T>::kill();
+                                    if let Some(started_at) = MigrationStartedAt::<T>::take() {
+                                        let now = <frame_system::Pallet<T>>::block_number();
+                                        MigrationDurations::<T>::insert(
+                                            migratable::storage_version_number(in_progress_version),
+                                            frame_support::sp_runtime::Saturating::saturating_sub(now, started_at),
+                                        );
+                                    }
+                                    migratable::charge_weight(&mut weight_left, &mut extra_weight, advance_weight);
+                                    migratable::log::info!(
+                                        target: LOG_TARGET,
+                                        "{name}: Migration {:?} completed in {} steps",
+                                        in_progress_version,
+                                        migratable::accrue_steps(MigrationStepsDone::<T>::take(), steps_done),
+                                    );
+                                    <T::MigrationObserver as migratable::MigrationObserver>::on_version_completed(
+                                        in_progress_version,
+                                    );
+                                    // the no-op migrations right after are completed at once
+                                    let last_version = T::Migrations::skip_noops(
+                                        in_progress_version,
+                                        Self::target_version(),
+                                    );
+                                    if last_version != in_progress_version {
+                                        last_version.put::<Pallet<T>>();
+                                        migratable::charge_weight(&mut weight_left, &mut extra_weight, noops_weight);
+                                        migratable::log::info!(
+                                            target: LOG_TARGET,
+                                            "{name}: Skipped no-op migrations up to {:?}",
+                                            last_version
+                                        );
+                                        let first_noop = migratable::storage_version_number(in_progress_version) + 1;
+                                        for version in first_noop..=migratable::storage_version_number(last_version) {
+                                            <T::MigrationObserver as migratable::MigrationObserver>::on_version_completed(
+                                                frame_support::traits::StorageVersion::new(version),
+                                            );
+                                        }
+                                    }
+                                    if let Some(next_version) =
+                                        Self::next_version_to_migrate(last_version)
                                     {
                                         migratable::log::info!(
                                             target: LOG_TARGET,
                                             "{name}: Next migratable is {:?},",
-                                            in_progress_version + 1
+                                            next_version
                                         );
-                                        *progress =
-                                            Some(T::Migrations::new(in_progress_version + 1));
-                                        migratable::MigrateResult::InProgress { steps_done }
+                                        match T::Migrations::start(next_version) {
+                                            Ok((cursor, start_weight)) => {
+                                                migratable::charge_weight(&mut weight_left, &mut extra_weight, start_weight);
+                                                *progress = Some(cursor);
+                                                migratable::MigrateResult::InProgress { steps_done }
+                                            }
+                                            Err(error) => {
+                                                frame_support::defensive!(
+                                                    migratable::MigrateError::StartFailed.as_str(),
+                                                    error,
+                                                );
+                                                // An empty cursor never decodes, so the migration
+                                                // is halted with the calls blocked, instead of
+                                                // being completed, until the cursor is replaced.
+                                                *progress = Some(migratable::Cursor::default());
+                                                migratable::MigrateResult::Failed {
+                                                    reason: migratable::MigrateError::StartFailed.as_str(),
+                                                }
+                                            }
+                                        }
                                     } else {
                                         migratable::log::info!(
                                             target: LOG_TARGET,
                                             "{name}: All migrations done. At version {:?},",
-                                            in_progress_version
+                                            last_version
                                         );
                                         *progress = None;
+                                        <T::MigrationObserver as migratable::MigrationObserver>::on_all_completed();
+                                        migratable::charge_weight(
+                                            &mut weight_left,
+                                            &mut extra_weight,
+                                            T::on_migrations_completed(),
+                                        );
                                         migratable::MigrateResult::Completed
                                     }
                                 }
                             };
 
-                            (result, weight_limit.saturating_sub(weight_left))
+                            let mut weight = migratable::consumed_weight(weight_limit, weight_left);
+                            migratable::add_weight(&mut weight, extra_weight);
+                            let mut weight_consumed = MigrationWeightConsumed::<T>::get();
+                            migratable::add_weight(&mut weight_consumed, weight);
+                            if progress.is_some() {
+                                MigrationWeightConsumed::<T>::put(weight_consumed);
+                            } else {
+                                migratable::log::info!(
+                                    target: LOG_TARGET,
+                                    "{name}: Migrations consumed {} in total",
+                                    migratable::LogWeight(weight_consumed)
+                                );
+                                MigrationWeightConsumed::<T>::kill();
+                            }
+                            if T::OFFCHAIN_INDEX_PROGRESS {
+                                migratable::index_progress(
+                                    name,
+                                    <frame_system::Pallet<T>>::block_number(),
+                                    &migratable::MigrationProgressRecord {
+                                        version: migratable::storage_version_number(
+                                            in_progress_version,
+                                        ),
+                                        steps_done,
+                                        weight_consumed,
+                                    },
+                                );
+                            }
+                            migratable::budget::MigrationWeightBudget::<T>::consume(weight);
+                            (result, weight)
                         })
                     }
 
+                    /// Checks the invariants of `version`, which must be already migrated.
+                    pub(crate) fn verify(version: u16) -> Result<(), migratable::MigrateError> {
+                        let name = <Pallet<T>>::name();
+                        let storage_version = frame_support::traits::StorageVersion::new(version);
+                        frame_support::ensure!(
+                            storage_version <= <Pallet<T>>::on_chain_storage_version(),
+                            migratable::MigrateError::NotMigrated(version)
+                        );
+                        T::Migrations::verify(storage_version).map_err(|reason| {
+                            migratable::log::error!(
+                                target: LOG_TARGET,
+                                "{name}: Verification of {:?} failed: {}",
+                                storage_version,
+                                reason,
+                            );
+                            migratable::MigrateError::VerificationFailed
+                        })?;
+                        #verified_event
+                        Ok(())
+                    }
+
+                    /// Schedules the migrations to start at `block`, e.g. from the runtime upgrade
+                    /// introducing them. `None` lets them run right away.
+                    pub fn set_start_block(
+                        block: Option<frame_system::pallet_prelude::BlockNumberFor<T>>,
+                    ) {
+                        MigrationStartBlock::<T>::set(block);
+                    }
+
+                    /// Returns the weight `migrate` charges out of its weight limit before
+                    /// running any step: its own overhead, the updates of `MigrationWeightConsumed`,
+                    /// `MigrationStepsDone`, `MigrationStepFailures`, `MigrationStepsInBlock`, the
+                    /// `MigrationStartedInUpgrade`, the start block, `MigrationStartedAt` and the migration weight
+                    /// budget.
+                    pub fn migrate_overhead() -> frame_support::weights::Weight {
+                        let mut weight = migratable::weights::SubstrateWeight::<T>::migrate();
+                        migratable::add_weight(
+                            &mut weight,
+                            <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get()
+                                .reads_writes(8, 7),
+                        );
+                        migratable::add_weight(
+                            &mut weight,
+                            migratable::budget::MigrationWeightBudget::<T>::weight(),
+                        );
+                        weight
+                    }
+
+                    /// Returns the weight consumed so far by the migration in progress.
+                    pub fn weight_consumed() -> frame_support::weights::Weight {
+                        MigrationWeightConsumed::<T>::get()
+                    }
+
+                    /// Returns the number of blocks the given version took to complete, if it was
+                    /// migrated by this pallet.
+                    pub fn migration_duration(
+                        version: u16,
+                    ) -> Option<frame_system::pallet_prelude::BlockNumberFor<T>> {
+                        MigrationDurations::<T>::get(version)
+                    }
+
+                    /// Removes up to `limit` entries of `MigrationDurations`, resuming from
+                    /// `maybe_cursor` if given, returning the number of entries removed, the cursor
+                    /// to resume from if any entry is left, and the weight consumed.
+                    pub fn clear_history(
+                        limit: u32,
+                        maybe_cursor: Option<&[u8]>,
+                    ) -> (
+                        u32,
+                        Option<frame_support::sp_std::vec::Vec<u8>>,
+                        frame_support::weights::Weight,
+                    ) {
+                        let result = MigrationDurations::<T>::clear(limit, maybe_cursor);
+                        migratable::log::info!(
+                            target: LOG_TARGET,
+                            "{}: Removed {} entries of the migration history",
+                            <Pallet<T>>::name(),
+                            result.unique,
+                        );
+                        let weight = <<T as frame_system::Config>::DbWeight as frame_support::traits::Get<_>>::get()
+                            .reads_writes(result.loops.into(), result.unique.into());
+                        #history_cleared_event
+                        (result.unique, result.maybe_cursor, weight)
+                    }
+
+                    #deposit_event
+
                     pub(crate) fn ensure_migrated() -> frame_support::dispatch::DispatchResult {
                         if Self::in_progress() {
-                            Err(frame_support::sp_runtime::DispatchError::Other(
-                                "There is a migration in progress",
-                            ))
+                            Err(migratable::MigrateError::MigrationInProgress.into())
                         } else {
                             Ok(())
                         }
                     }
 
-                    pub(crate) fn in_progress() -> bool {
-                        MigrationInProgress::<T>::exists()
+                    #cursor_accessors
+
+                    /// Returns the version being migrated, if a migration is in progress.
+                    pub fn in_progress_version() -> Option<frame_support::traits::StorageVersion> {
+                        // if a migration is running it is always upgrading to the next version
+                        if Self::in_progress() {
+                            migratable::next_version(<Pallet<T>>::on_chain_storage_version())
+                        } else {
+                            None
+                        }
+                    }
+
+                    /// Whether `version` has been applied, i.e. the on-chain storage version
+                    /// reached it.
+                    pub fn version_applied(version: frame_support::traits::StorageVersion) -> bool {
+                        <Pallet<T>>::on_chain_storage_version() >= version
+                    }
+
+                    /// Whether `version` is yet to be applied by the migrations of this runtime,
+                    /// i.e. it is not applied and doesn't go beyond the target version.
+                    pub fn version_pending(version: frame_support::traits::StorageVersion) -> bool {
+                        !Self::version_applied(version) && version <= Self::target_version()
+                    }
+
+                    /// Returns a preview of the next step of the migration in progress, without
+                    /// running it.
+                    pub fn peek() -> Option<migratable::StepPreview> {
+                        let version = Self::in_progress_version()?;
+                        let cursor = Self::cursor()?;
+                        Some(migratable::StepPreview {
+                            version: migratable::storage_version_number(version),
+                            position: T::Migrations::describe_position(version, &cursor),
+                        })
+                    }
+
+                    /// Returns the version to migrate after `version`, unless `version` is the
+                    /// target version.
+                    fn next_version_to_migrate(
+                        version: frame_support::traits::StorageVersion,
+                    ) -> Option<frame_support::traits::StorageVersion> {
+                        migratable::next_version(version)
+                            .filter(|next_version| *next_version <= Self::target_version())
+                    }
+
+                    /// Starts the migration of the version returned by `Config::on_no_migration`, if
+                    /// any, returning its cursor and the weight consumed.
+                    fn start_on_demand() -> Option<(migratable::Cursor, frame_support::weights::Weight)> {
+                        let version = T::on_no_migration()?;
+                        let storage_version = <Pallet<T>>::on_chain_storage_version();
+                        if Self::next_version_to_migrate(storage_version) != Some(version) {
+                            migratable::log::error!(
+                                target: LOG_TARGET,
+                                "{}: Can't start the migration of {:?} at version {:?}",
+                                <Pallet<T>>::name(),
+                                version,
+                                storage_version,
+                            );
+                            return None;
+                        }
+                        match T::Migrations::start(version) {
+                            Ok((cursor, weight)) => {
+                                migratable::log::info!(
+                                    target: LOG_TARGET,
+                                    "{}: Starting the migration of {:?} on demand",
+                                    <Pallet<T>>::name(),
+                                    version,
+                                );
+                                Some((cursor, weight))
+                            }
+                            Err(error) => {
+                                frame_support::defensive!(
+                                    "Failed to start the migration on demand",
+                                    error,
+                                );
+                                None
+                            }
+                        }
+                    }
+
+                    /// Sets the on-chain storage version without running any migration, unless a
+                    /// migration is in progress.
+                    pub(crate) fn force_set_storage_version(
+                        version: u16,
+                    ) -> frame_support::dispatch::DispatchResult {
+                        Self::ensure_migrated()?;
+                        let version = frame_support::traits::StorageVersion::new(version);
+                        migratable::log::warn!(
+                            target: LOG_TARGET,
+                            "{}: Forcing storage version from {:?} to {:?}",
+                            <Pallet<T>>::name(),
+                            <Pallet<T>>::on_chain_storage_version(),
+                            version,
+                        );
+                        version.put::<Pallet<T>>();
+                        Ok(())
+                    }
+
+                    /// Replaces the cursor of the migration in progress, after checking that it
+                    /// decodes as the step of the version in progress.
+                    pub(crate) fn force_set_cursor(
+                        cursor: migratable::Cursor,
+                    ) -> frame_support::dispatch::DispatchResult {
+                        let version = Self::in_progress_version().ok_or(
+                            frame_support::sp_runtime::DispatchError::Other("No migration in progress"),
+                        )?;
+                        frame_support::ensure!(
+                            T::Migrations::validate_cursor(version, &cursor),
+                            migratable::MigrateError::CursorDecode
+                        );
+                        migratable::log::warn!(
+                            target: LOG_TARGET,
+                            "{}: Forcing the cursor of {:?}",
+                            <Pallet<T>>::name(),
+                            version,
+                        );
+                        Self::set_cursor(Some(cursor));
+                        // resumes a migration halted by its failed steps
+                        MigrationStepFailures::<T>::kill();
+                        Ok(())
+                    }
+
+                    /// Returns the version the migrations stop at: `Config::TARGET_VERSION` if set,
+                    /// or the current storage version of the pallet otherwise, without ever going
+                    /// beyond the last version of `Config::Migrations`.
+                    pub fn target_version() -> frame_support::traits::StorageVersion {
+                        let target = T::TARGET_VERSION
+                            .unwrap_or_else(|| <Pallet<T>>::current_storage_version());
+                        core::cmp::min(target, T::Migrations::last_version())
+                    }
+
+                    /// Returns the number of versions left to migrate to reach the target version,
+                    /// including the one in progress.
+                    pub fn versions_remaining() -> u16 {
+                        migratable::storage_version_number(Self::target_version()).saturating_sub(
+                            migratable::storage_version_number(<Pallet<T>>::on_chain_storage_version()),
+                        )
+                    }
+
+                    /// Returns the estimate of the items left to migrate by the version in
+                    /// progress, see `MigrationStep::estimate_remaining_items`, or `None` if no
+                    /// migration is in progress or the step doesn't estimate it.
+                    ///
+                    /// The within-version counterpart of `versions_remaining`.
+                    pub fn remaining_items() -> Option<u64> {
+                        let version = Self::in_progress_version()?;
+                        let cursor = Self::cursor()?;
+                        T::Migrations::estimate_remaining_items(version, &cursor)
+                    }
+
+                    /// Returns the number of versions of `Config::Migrations`.
+                    ///
+                    /// Along with `versions_remaining`, drives a progress bar across versions.
+                    pub fn versions_total() -> u16 {
+                        let (first, last) = T::Migrations::VERSION_RANGE;
+                        if (first, last) == (0, 0) {
+                            0
+                        } else {
+                            last - first + 1
+                        }
+                    }
+
+                    /// Simulates, block by block, the migrations left to reach the current storage
+                    /// version, given an estimate of the steps needed by each version.
+                    #[cfg(feature = "std")]
+                    pub fn simulate(
+                        input: &migratable::simulation::SimulationInput,
+                    ) -> Vec<migratable::simulation::BlockPlan> {
+                        // no version follows the last one
+                        let Some(next_version) =
+                            migratable::next_version(<Pallet<T>>::on_chain_storage_version())
+                        else {
+                            return Vec::new();
+                        };
+                        migratable::simulation::simulate::<T::Migrations>(
+                            migratable::storage_version_number(next_version),
+                            migratable::storage_version_number(Self::target_version()),
+                            migratable::weights::SubstrateWeight::<T>::migrate(),
+                            input,
+                        )
+                    }
+
+                    /// Returns the status of the migrations of this pallet.
+                    pub fn status() -> migratable::MigrationStatus {
+                        match Self::cursor() {
+                            Some(cursor) => migratable::MigrationStatus::InProgress {
+                                version: migratable::storage_version_number(
+                                    <Pallet<T>>::on_chain_storage_version(),
+                                )
+                                .saturating_add(1),
+                                cursor_len: cursor.len() as u32,
+                            },
+                            None if <Pallet<T>>::on_chain_storage_version()
+                                < Self::target_version() =>
+                            {
+                                migratable::MigrationStatus::NotStarted
+                            }
+                            None => migratable::MigrationStatus::Completed,
+                        }
                     }
                 }
             };
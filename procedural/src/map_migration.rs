@@ -0,0 +1,141 @@
+use quote::quote;
+
+/// Name of the field holding the raw key of the last migrated entry.
+const LAST_KEY_FIELD: &str = "last_key";
+
+/// Arguments of the `#[map_migration(..)]` attribute.
+#[derive(Default)]
+struct MapMigrationArgs {
+    version: Option<syn::LitInt>,
+    old: Option<syn::Type>,
+    new: Option<syn::Type>,
+    transform: Option<syn::Expr>,
+    weight: Option<syn::Expr>,
+    max_key_len: Option<syn::LitInt>,
+}
+
+impl MapMigrationArgs {
+    fn parse(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("version") {
+            self.version = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("old") {
+            self.old = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("new") {
+            self.new = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("transform") {
+            self.transform = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("weight") {
+            self.weight = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("max_key_len") {
+            self.max_key_len = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("unsupported map_migration argument"));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the length of the SCALE compact encoding of `value`.
+fn compact_len(value: u32) -> u32 {
+    match value {
+        0..=0x3f => 1,
+        0x40..=0x3fff => 2,
+        0x4000..=0x3fff_ffff => 4,
+        _ => 5,
+    }
+}
+
+fn missing(input: &syn::DeriveInput, name: &str) -> syn::Error {
+    syn::Error::new_spanned(
+        &input.ident,
+        format!("missing `{name}` in the `#[map_migration(..)]` attribute"),
+    )
+}
+
+pub fn expand(input: syn::DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let mut args = MapMigrationArgs::default();
+    for attr in input.attrs.iter() {
+        if attr.path().is_ident("map_migration") {
+            attr.parse_nested_meta(|meta| args.parse(meta))?;
+        }
+    }
+    let version = args.version.ok_or_else(|| missing(&input, "version"))?;
+    let old = args.old.ok_or_else(|| missing(&input, "old"))?;
+    let new = args.new.ok_or_else(|| missing(&input, "new"))?;
+    let transform = args.transform.ok_or_else(|| missing(&input, "transform"))?;
+    let weight = args.weight.ok_or_else(|| missing(&input, "weight"))?;
+    let max_key_len = args
+        .max_key_len
+        .ok_or_else(|| missing(&input, "max_key_len"))?;
+
+    let syn::Data::Struct(syn::DataStruct {
+        fields: syn::Fields::Named(fields),
+        ..
+    }) = &input.data
+    else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "MapMigration can only be derived for structs with named fields",
+        ));
+    };
+    if !fields.named.iter().any(|field| {
+        field
+            .ident
+            .as_ref()
+            .is_some_and(|ident| ident == LAST_KEY_FIELD)
+    }) {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("MapMigration requires a `{LAST_KEY_FIELD}: Option<Vec<u8>>` field"),
+        ));
+    }
+    let field_idents = fields.named.iter().map(|field| &field.ident);
+
+    // `Option` tag, compact length prefix and the key itself
+    let max_key_len_value = max_key_len.base10_parse::<u32>()?;
+    let max_cursor_len = 1 + compact_len(max_key_len_value) + max_key_len_value;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::default::Default for #ident #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#field_idents: ::core::default::Default::default(),)*
+                }
+            }
+        }
+
+        impl #impl_generics migratable::MigrationStep for #ident #ty_generics #where_clause {
+            const VERSION: u16 = #version;
+            const MAX_CURSOR_LEN: u32 = #max_cursor_len;
+
+            fn max_step_weight() -> frame_support::weights::Weight {
+                #weight
+            }
+
+            fn step(&mut self) -> (migratable::IsFinished, frame_support::weights::Weight) {
+                let mut iter = match self.last_key.take() {
+                    Some(last_key) => <#old>::iter_from(last_key),
+                    None => <#old>::iter(),
+                };
+                match iter.next() {
+                    Some((key, value)) => {
+                        // migrated entries are removed, so resuming from the start is still
+                        // correct when the key doesn't fit into the cursor
+                        let last_key = iter.last_raw_key();
+                        self.last_key = (last_key.len() <= #max_key_len_value as usize)
+                            .then(|| last_key.to_vec());
+                        <#old>::remove(&key);
+                        if let Some((new_key, new_value)) = #transform(key, value) {
+                            <#new>::insert(new_key, new_value);
+                        }
+                        (migratable::IsFinished::No, #weight)
+                    }
+                    None => (migratable::IsFinished::Yes, #weight),
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,13 @@
+#[migratable_procedural::pallet(calls)]
+mod pallet {
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        #[pallet::call_index(254)]
+        #[pallet::weight(0)]
+        pub fn do_something(origin: OriginFor<T>) -> DispatchResult {
+            Ok(())
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+#[migratable_procedural::config]
+pub trait Config<I: 'static = ()>: frame_system::Config
+where
+    Self: Sized,
+{
+    type RuntimeEvent;
+    type Migrations;
+}
+
+fn main() {}
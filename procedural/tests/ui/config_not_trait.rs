@@ -0,0 +1,4 @@
+#[migratable_procedural::config]
+pub struct Config;
+
+fn main() {}
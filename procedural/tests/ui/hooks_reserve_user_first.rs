@@ -0,0 +1,4 @@
+#[migratable_procedural::hooks(order = user_first, reserve_for_user = Weight::zero())]
+impl Hooks {}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#[derive(migratable_procedural::MapMigration)]
+#[map_migration(
+    old = OldLedger,
+    new = Ledger,
+    transform = into_ledger,
+    weight = Weight::zero(),
+    max_key_len = 80,
+)]
+struct MigrateLedger {
+    last_key: Option<Vec<u8>>,
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+#[derive(migratable_procedural::MapMigration)]
+#[map_migration(
+    version = 2,
+    old = OldLedger,
+    new = Ledger,
+    transform = into_ledger,
+    weight = Weight::zero(),
+    max_key_len = 80,
+)]
+struct MigrateLedger {
+    cursor: Option<Vec<u8>>,
+}
+
+fn main() {}
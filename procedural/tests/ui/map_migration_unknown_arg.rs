@@ -0,0 +1,7 @@
+#[derive(migratable_procedural::MapMigration)]
+#[map_migration(version = 2, hasher = Twox64Concat)]
+struct MigrateLedger {
+    last_key: Option<Vec<u8>>,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+#[migratable_procedural::hooks]
+impl Hooks {
+    fn on_idle(_block: u32) -> u64 {
+        0
+    }
+}
+
+fn main() {}
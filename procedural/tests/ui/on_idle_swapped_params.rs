@@ -0,0 +1,8 @@
+#[migratable_procedural::hooks]
+impl Hooks {
+    fn on_idle(remaining_weight: Weight, _block: BlockNumberFor<T>) -> Weight {
+        remaining_weight
+    }
+}
+
+fn main() {}
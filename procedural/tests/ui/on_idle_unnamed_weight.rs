@@ -0,0 +1,8 @@
+#[migratable_procedural::hooks]
+impl Hooks {
+    fn on_idle(_block: BlockNumberFor<T>, _: Weight) -> Weight {
+        Weight::zero()
+    }
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[migratable_procedural::pallet]
+mod pallet;
+
+fn main() {}
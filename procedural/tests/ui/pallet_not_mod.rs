@@ -0,0 +1,4 @@
+#[migratable_procedural::pallet]
+pub struct Pallet;
+
+fn main() {}
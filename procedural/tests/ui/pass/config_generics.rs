@@ -0,0 +1,25 @@
+use frame_support::{
+    traits::{Get, StorageVersion},
+    weights::Weight,
+};
+
+/// An instantiable config, with a supertrait and a where clause.
+#[migratable::config]
+pub trait Config<I: 'static = ()>: frame_system::Config
+where
+    Self::AccountId: Ord,
+{
+    type MaxItems: Get<u32>;
+}
+
+#[allow(dead_code)]
+fn injected_items<T: Config<I>, I: 'static>() -> (u32, Weight, Option<StorageVersion>)
+where
+    T::AccountId: Ord,
+{
+    // the supertrait is kept
+    let _ = frame_system::Pallet::<T>::block_number();
+    (T::MaxItems::get(), T::max_migrate_call_weight(), T::TARGET_VERSION)
+}
+
+fn main() {}
@@ -0,0 +1,33 @@
+use frame_support::{storage_alias, weights::Weight, Twox64Concat};
+use migratable::{MapMigration, MigrationStep};
+use parity_scale_codec::{Decode, Encode};
+
+#[storage_alias]
+type OldLedger = StorageMap<Example, Twox64Concat, u32, u32>;
+
+#[storage_alias]
+type Ledger = StorageMap<Example, Twox64Concat, u32, u64>;
+
+fn into_ledger(key: u32, value: u32) -> Option<(u32, u64)> {
+    Some((key, value.into()))
+}
+
+// any field other than `last_key` only needs to implement `Default`
+#[derive(Encode, Decode, MapMigration)]
+#[map_migration(
+    version = 2,
+    old = OldLedger,
+    new = Ledger,
+    transform = into_ledger,
+    weight = Weight::from_parts(10, 0),
+    max_key_len = 80,
+)]
+struct MigrateLedger {
+    last_key: Option<Vec<u8>>,
+    migrated: u32,
+}
+
+fn main() {
+    let _ = MigrateLedger::default();
+    assert_eq!(<MigrateLedger as MigrationStep>::VERSION, 2);
+}
@@ -0,0 +1,6 @@
+#[migratable_procedural::hooks]
+impl Hooks {
+    fn on_initialise() {}
+}
+
+fn main() {}
@@ -0,0 +1,120 @@
+//! A migration weight budget shared by every pallet of the runtime.
+//!
+//! Each pallet drives its migrations from its own `on_idle`, so without a shared budget the total
+//! weight spent on migrations in a block grows with the number of pallets migrating at once. Once
+//! a cap is set through [`MigrationWeightBudget::set_cap`], every `migrate` call is limited to the
+//! budget left in the current block, and decrements it by the weight it consumed.
+//!
+//! The budget left is cleared in `on_finalize`, so that it is never persisted, as long as
+//! [`MigrationWeightBudget`] is added next to `AllPalletsWithSystem` in `Executive`:
+//!
+//! ```ignore
+//! pub type Executive = frame_executive::Executive<
+//!     Runtime,
+//!     Block,
+//!     frame_system::ChainContext<Runtime>,
+//!     Runtime,
+//!     (AllPalletsWithSystem, migratable::budget::MigrationWeightBudget<Runtime>),
+//! >;
+//! ```
+
+use frame_support::{
+    pallet_prelude::{OptionQuery, Weight},
+    storage_alias,
+    traits::{Get, OffchainWorker, OnFinalize, OnIdle, OnInitialize, OnRuntimeUpgrade},
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+
+/// The cap of the budget, stored under the `MigratableBudget` prefix as if it were a pallet.
+#[storage_alias]
+type Cap = StorageValue<MigratableBudget, Weight, OptionQuery>;
+
+/// The budget left, along with the block it belongs to.
+#[storage_alias]
+type Remaining<T: frame_system::Config> =
+    StorageValue<MigratableBudget, (BlockNumberFor<T>, Weight), OptionQuery>;
+
+/// The migration weight budget of the current block, see the [module docs](self).
+pub struct MigrationWeightBudget<T>(core::marker::PhantomData<T>);
+
+impl<T: frame_system::Config> MigrationWeightBudget<T> {
+    /// Caps the weight spent on migrations in every block, across all pallets. `None` removes the
+    /// cap.
+    ///
+    /// Meant to be called from genesis, a runtime upgrade or a governance call.
+    pub fn set_cap(cap: Option<Weight>) {
+        Cap::set(cap);
+        Remaining::<T>::kill();
+    }
+
+    /// Returns the cap of the weight spent on migrations in every block, if any.
+    pub fn cap() -> Option<Weight> {
+        Cap::get()
+    }
+
+    /// Returns the budget left in the current block, or `None` if there is no cap.
+    pub fn remaining() -> Option<Weight> {
+        let cap = Self::cap()?;
+        let now = frame_system::Pallet::<T>::block_number();
+        match Remaining::<T>::get() {
+            Some((block, remaining)) if block == now => Some(remaining),
+            _ => Some(cap),
+        }
+    }
+
+    /// Limits `weight_limit` to the budget left in the current block.
+    pub fn clip(weight_limit: Weight) -> Weight {
+        Self::remaining().map_or(weight_limit, |remaining| weight_limit.min(remaining))
+    }
+
+    /// Decrements the budget left in the current block by `weight`.
+    pub fn consume(weight: Weight) {
+        if let Some(remaining) = Self::remaining() {
+            let now = frame_system::Pallet::<T>::block_number();
+            Remaining::<T>::put((now, remaining.saturating_sub(weight)));
+        }
+    }
+
+    /// Returns the worst case weight of [`MigrationWeightBudget::clip`] followed by
+    /// [`MigrationWeightBudget::consume`], which read the cap and the budget left twice.
+    pub fn weight() -> Weight {
+        T::DbWeight::get().reads_writes(4, 1)
+    }
+}
+
+impl<T: frame_system::Config> OnInitialize<BlockNumberFor<T>> for MigrationWeightBudget<T> {
+    fn on_initialize(_block: BlockNumberFor<T>) -> Weight {
+        // the cap is read here and in `on_finalize`, which only clears the budget left under a cap
+        match Self::cap() {
+            None => T::DbWeight::get().reads(2),
+            Some(_) => T::DbWeight::get().reads_writes(2, 1),
+        }
+    }
+}
+
+impl<T: frame_system::Config> OnFinalize<BlockNumberFor<T>> for MigrationWeightBudget<T> {
+    fn on_finalize(_block: BlockNumberFor<T>) {
+        if Self::cap().is_some() {
+            Remaining::<T>::kill();
+        }
+    }
+}
+
+// the other hooks required by `Executive` do nothing
+impl<T: frame_system::Config> OnIdle<BlockNumberFor<T>> for MigrationWeightBudget<T> {}
+
+impl<T: frame_system::Config> OffchainWorker<BlockNumberFor<T>> for MigrationWeightBudget<T> {}
+
+impl<T: frame_system::Config> OnRuntimeUpgrade for MigrationWeightBudget<T> {}
+
+#[cfg(feature = "try-runtime")]
+impl<T: frame_system::Config> frame_support::traits::TryState<BlockNumberFor<T>>
+    for MigrationWeightBudget<T>
+{
+    fn try_state(
+        _block: BlockNumberFor<T>,
+        _targets: frame_support::traits::TryStateSelect,
+    ) -> Result<(), sp_runtime::TryRuntimeError> {
+        Ok(())
+    }
+}
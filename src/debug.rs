@@ -0,0 +1,27 @@
+//! Diagnostics of the persisted [`Cursor`], to investigate stuck migrations.
+
+use crate::{Cursor, MigrateSequence};
+use alloc::{format, string::String};
+use core::fmt::Write;
+use frame_support::pallet_prelude::StorageVersion;
+
+/// Returns the cursor as a hex string, along with its length.
+pub fn describe_cursor(cursor: &Cursor) -> String {
+    let mut hex = String::with_capacity(2 + 2 * cursor.len());
+    hex.push_str("0x");
+    for byte in cursor.iter() {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    format!("{hex} ({} bytes)", cursor.len())
+}
+
+/// Same as [`describe_cursor`], also decoding the cursor as the step of `version` in `S` and
+/// appending its [`MigrationStep::debug_description`](crate::MigrationStep::debug_description).
+pub fn describe_cursor_for<S: MigrateSequence>(version: StorageVersion, cursor: &Cursor) -> String {
+    let hex = describe_cursor(cursor);
+    match S::describe_cursor(version, cursor) {
+        Ok(Some(description)) => format!("{description} = {hex}"),
+        Ok(None) => hex,
+        Err(error) => format!("{hex}, which can't be decoded for {version:?}: {error:?}"),
+    }
+}
@@ -0,0 +1,274 @@
+//! A central driver of the migrations of several pallets, as an alternative to the `hooks` macro.
+//!
+//! [`MigrationExecutor`] runs the migrations of the given pallets in order from `on_idle`, sharing
+//! the remaining weight of the block between them. It is placed next to the pallets in `Executive`:
+//!
+//! ```ignore
+//! pub type Executive = frame_executive::Executive<
+//!     Runtime,
+//!     Block,
+//!     frame_system::ChainContext<Runtime>,
+//!     Runtime,
+//!     (
+//!         AllPalletsWithSystem,
+//!         migratable::executor::MigrationExecutor<(
+//!             pallet_a::Migration<Runtime>,
+//!             pallet_b::Migration<Runtime>,
+//!         )>,
+//!     ),
+//!     (pallet_a::Migration<Runtime>, pallet_b::Migration<Runtime>),
+//! >;
+//! ```
+//!
+//! Pallets driven this way should not use the `hooks` macro as well, which would run their
+//! migrations twice per block. Their `integrity_test` and `try_state` checks are then not wired
+//! either, and can be called from the pallet's own hooks, or for all the pallets at once with
+//! [`integrity_test_all!`](crate::integrity_test_all).
+//!
+//! ## Ordered migrations
+//!
+//! The migrations of pallets depending on each other can be run strictly in order with
+//! [`OrderedMigrations`], usually defined with [`ordered_migrations!`](crate::ordered_migrations).
+//! On runtime upgrade, it only starts the migrations of the first pallet left to migrate. The
+//! migrations of every other pallet are started, from `on_idle`, once the pallets before it have
+//! completed theirs and the remaining weight covers their start. A pallet whose migrations don't
+//! support the upgrade from its on-chain storage version is never started, nor holds back the
+//! pallets after it. The `try-runtime` checks of every pallet are run on runtime upgrade:
+//!
+//! ```ignore
+//! migratable::ordered_migrations! {
+//!     pub type RuntimeMigrations = (pallet_a::Migration<Runtime>, pallet_b::Migration<Runtime>);
+//! }
+//!
+//! pub type Executive = frame_executive::Executive<
+//!     Runtime,
+//!     Block,
+//!     frame_system::ChainContext<Runtime>,
+//!     Runtime,
+//!     (AllPalletsWithSystem, migratable::executor::MigrationExecutor<RuntimeMigrations>),
+//!     RuntimeMigrations,
+//! >;
+//! ```
+
+use crate::{MigrationStatus, PalletMigration};
+use frame_support::{
+    pallet_prelude::Weight,
+    traits::{OffchainWorker, OnFinalize, OnIdle, OnInitialize, OnRuntimeUpgrade},
+};
+#[cfg(feature = "try-runtime")]
+use parity_scale_codec::{Decode, Encode};
+#[cfg(feature = "try-runtime")]
+use sp_std::vec::Vec;
+
+/// Drives the migrations of `Pallets`, a tuple of the `Migration` structs of the pallets, from
+/// `on_idle`. See the [module docs](self).
+pub struct MigrationExecutor<Pallets>(core::marker::PhantomData<Pallets>);
+
+/// A set of pallet migrations driven in order by the [`MigrationExecutor`].
+pub trait MigrationExecutorSet {
+    /// Runs the migrations of every pallet of the set in order, with up to `remaining_weight`,
+    /// reducing it by the weight consumed, which is returned.
+    fn migrate_idle(remaining_weight: &mut Weight) -> Weight;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+#[tuple_types_custom_trait_bound(PalletMigration)]
+impl MigrationExecutorSet for Tuple {
+    fn migrate_idle(remaining_weight: &mut Weight) -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!(
+            #(
+                weight.saturating_accrue(Tuple::migrate_idle(remaining_weight));
+            )*
+        );
+        weight
+    }
+}
+
+impl<BlockNumber, Pallets: MigrationExecutorSet> OnIdle<BlockNumber>
+    for MigrationExecutor<Pallets>
+{
+    fn on_idle(_block: BlockNumber, mut remaining_weight: Weight) -> Weight {
+        Pallets::migrate_idle(&mut remaining_weight)
+    }
+}
+
+// the other hooks required by `Executive` do nothing
+impl<BlockNumber, Pallets> OnInitialize<BlockNumber> for MigrationExecutor<Pallets> {}
+
+impl<BlockNumber, Pallets> OnFinalize<BlockNumber> for MigrationExecutor<Pallets> {}
+
+impl<BlockNumber, Pallets> OffchainWorker<BlockNumber> for MigrationExecutor<Pallets> {}
+
+impl<Pallets> OnRuntimeUpgrade for MigrationExecutor<Pallets> {}
+
+#[cfg(feature = "try-runtime")]
+impl<BlockNumber, Pallets> frame_support::traits::TryState<BlockNumber>
+    for MigrationExecutor<Pallets>
+{
+    fn try_state(
+        _block: BlockNumber,
+        _targets: frame_support::traits::TryStateSelect,
+    ) -> Result<(), sp_runtime::TryRuntimeError> {
+        Ok(())
+    }
+}
+
+/// Starts the migrations of `Pallets`, a tuple of the `Migration` structs of the pallets, in order
+/// on runtime upgrade, and drives them one pallet after the other through a [`MigrationExecutor`].
+/// See the [module docs](self).
+pub struct OrderedMigrations<Pallets>(core::marker::PhantomData<Pallets>);
+
+/// A set of pallet migrations run strictly in order by [`OrderedMigrations`].
+pub trait OrderedMigrationSet {
+    /// Starts the migrations of the pallets of the set in order, returning the weight consumed.
+    ///
+    /// A pallet is skipped if `blocked` is set, which it sets unless its migrations are
+    /// completed, e.g. right away under `try-runtime`, or can't start at all, so that the pallets
+    /// after it are started once it completes.
+    fn on_runtime_upgrade(blocked: &mut bool) -> Weight;
+
+    /// Runs the migrations of the pallets of the set in order, with up to `remaining_weight`,
+    /// reducing it by the weight consumed, which is returned.
+    ///
+    /// A pallet is skipped if `blocked` is set. Otherwise, its migrations are started if they
+    /// are not yet and `remaining_weight` covers their start, and it sets `blocked` unless they
+    /// are completed or can't start at all, so that the pallets after it wait for their
+    /// completion.
+    fn migrate_idle(remaining_weight: &mut Weight, blocked: &mut bool) -> Weight;
+
+    /// Runs the `pre_upgrade` checks of every pallet of the set, returning their encoded states.
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<Vec<Vec<u8>>, sp_runtime::TryRuntimeError>;
+
+    /// Runs the `post_upgrade` checks of every pallet of the set, given the states returned by
+    /// [`OrderedMigrationSet::pre_upgrade`].
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade(states: Vec<Vec<u8>>) -> Result<(), sp_runtime::TryRuntimeError>;
+}
+
+/// Whether the migrations of `P` don't hold back the pallets after it: they are completed, or
+/// not started and never will be.
+fn is_done<P: PalletMigration>() -> bool {
+    match P::status() {
+        MigrationStatus::Completed => true,
+        MigrationStatus::NotStarted => !P::can_start(),
+        MigrationStatus::InProgress { .. } => false,
+    }
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+#[tuple_types_no_default_trait_bound]
+impl OrderedMigrationSet for Tuple {
+    for_tuples!( where #( Tuple: PalletMigration + OnRuntimeUpgrade )* );
+
+    fn on_runtime_upgrade(blocked: &mut bool) -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!(
+            #(
+                if !*blocked {
+                    weight.saturating_accrue(Tuple::on_runtime_upgrade());
+                    *blocked = !is_done::<Tuple>();
+                }
+            )*
+        );
+        weight
+    }
+
+    fn migrate_idle(remaining_weight: &mut Weight, blocked: &mut bool) -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!(
+            #(
+                if !*blocked {
+                    match Tuple::status() {
+                        // an upgrade that is not supported never starts, nor holds back the
+                        // pallets after it
+                        MigrationStatus::NotStarted if !Tuple::can_start() => {}
+                        // started once the pallets before it are completed, if it fits
+                        MigrationStatus::NotStarted
+                            if remaining_weight.any_lt(Tuple::start_weight()) =>
+                        {
+                            *blocked = true;
+                        }
+                        status => {
+                            if status == MigrationStatus::NotStarted {
+                                let start_weight = Tuple::on_runtime_upgrade();
+                                remaining_weight.saturating_reduce(start_weight);
+                                weight.saturating_accrue(start_weight);
+                            }
+                            weight.saturating_accrue(Tuple::migrate_idle(remaining_weight));
+                            *blocked = !is_done::<Tuple>();
+                        }
+                    }
+                }
+            )*
+        );
+        weight
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<Vec<Vec<u8>>, sp_runtime::TryRuntimeError> {
+        let mut states = Vec::new();
+        for_tuples!(
+            #(
+                states.push(Tuple::pre_upgrade()?);
+            )*
+        );
+        Ok(states)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade(states: Vec<Vec<u8>>) -> Result<(), sp_runtime::TryRuntimeError> {
+        let mut states = states.into_iter();
+        for_tuples!(
+            #(
+                Tuple::post_upgrade(states.next().ok_or("Missing the pre-upgrade state of a pallet")?)?;
+            )*
+        );
+        Ok(())
+    }
+}
+
+impl<Pallets: OrderedMigrationSet> MigrationExecutorSet for OrderedMigrations<Pallets> {
+    fn migrate_idle(remaining_weight: &mut Weight) -> Weight {
+        Pallets::migrate_idle(remaining_weight, &mut false)
+    }
+}
+
+impl<Pallets: OrderedMigrationSet> OnRuntimeUpgrade for OrderedMigrations<Pallets> {
+    fn on_runtime_upgrade() -> Weight {
+        Pallets::on_runtime_upgrade(&mut false)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade() -> Result<Vec<u8>, sp_runtime::TryRuntimeError> {
+        Pallets::pre_upgrade().map(|states| states.encode())
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade(state: Vec<u8>) -> Result<(), sp_runtime::TryRuntimeError> {
+        let states = <Vec<Vec<u8>>>::decode(&mut &state[..])
+            .map_err(|_| "Failed to decode the pre-upgrade states of the pallets")?;
+        Pallets::post_upgrade(states)
+    }
+}
+
+/// Defines [`OrderedMigrations`] as a type alias, running the migrations of the given pallets in
+/// the declared order. See the [module docs](crate::executor).
+///
+/// ```ignore
+/// migratable::ordered_migrations! {
+///     /// The migrations of the runtime, `pallet_b` depending on the migrations of `pallet_a`.
+///     pub type RuntimeMigrations = (pallet_a::Migration<Runtime>, pallet_b::Migration<Runtime>);
+/// }
+/// ```
+#[macro_export]
+macro_rules! ordered_migrations {
+    (
+        $(#[$attr:meta])*
+        $vis:vis type $name:ident = ($($pallet:ty),+ $(,)?);
+    ) => {
+        $(#[$attr])*
+        $vis type $name = $crate::executor::OrderedMigrations<($($pallet,)+)>;
+    };
+}
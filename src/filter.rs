@@ -0,0 +1,82 @@
+//! A call filter blocking the calls of the pallets whose migrations are in progress.
+//!
+//! [`MigrationCallFilter`] is meant to be the `BaseCallFilter` of the runtime, or part of it. While
+//! the migrations of one of the given pallets are in progress, it filters out the calls of that
+//! pallet, except the calls generated by the `calls` argument of the `pallet` macro, e.g.
+//! `migrate`, and the calls allowed by `Allowed`, matched by pallet and call:
+//!
+//! ```ignore
+//! pub struct AllowedDuringMigrations;
+//!
+//! impl Contains<RuntimeCall> for AllowedDuringMigrations {
+//!     fn contains(call: &RuntimeCall) -> bool {
+//!         matches!(call, RuntimeCall::Example(pallet_example::Call::pause { .. }))
+//!     }
+//! }
+//!
+//! impl frame_system::Config for Runtime {
+//!     type BaseCallFilter = migratable::filter::MigrationCallFilter<
+//!         (pallet_example::Migration<Runtime>, pallet_other::Migration<Runtime>),
+//!         AllowedDuringMigrations,
+//!     >;
+//!     // ...
+//! }
+//! ```
+//!
+//! The calls of the other pallets are never filtered out.
+
+use crate::{MigrationStatus, PalletMigration};
+use frame_support::traits::{Contains, GetCallMetadata, Nothing};
+
+/// The names of the calls generated by the `calls` argument of the `pallet` macro, which are
+/// needed to drive or fix the migrations, so they are never filtered out.
+pub const MIGRATION_CALLS: &[&str] = &[
+    "force_set_storage_version",
+    "migrate",
+    "verify_migration",
+    "set_migration_start_block",
+    "force_set_cursor",
+    "clear_migration_history",
+];
+
+/// Filters out the calls of `Pallets`, a tuple of the `Migration` structs of the pallets, while
+/// their migrations are in progress, unless allowed by `Allowed`. See the [module docs](self).
+pub struct MigrationCallFilter<Pallets, Allowed = Nothing>(
+    core::marker::PhantomData<(Pallets, Allowed)>,
+);
+
+/// A set of pallet migrations whose calls are filtered by the [`MigrationCallFilter`].
+pub trait MigratingPallets {
+    /// Returns whether the pallet named `pallet_name` is in the set, with its migrations in
+    /// progress.
+    fn is_migrating(pallet_name: &str) -> bool;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+#[tuple_types_custom_trait_bound(PalletMigration)]
+impl MigratingPallets for Tuple {
+    fn is_migrating(pallet_name: &str) -> bool {
+        for_tuples!(
+            #(
+                if Tuple::pallet_name() == pallet_name {
+                    return matches!(Tuple::status(), MigrationStatus::InProgress { .. })
+                }
+            )*
+        );
+        false
+    }
+}
+
+impl<Call, Pallets, Allowed> Contains<Call> for MigrationCallFilter<Pallets, Allowed>
+where
+    Call: GetCallMetadata,
+    Pallets: MigratingPallets,
+    Allowed: Contains<Call>,
+{
+    fn contains(call: &Call) -> bool {
+        let metadata = call.get_call_metadata();
+        MIGRATION_CALLS.contains(&metadata.function_name)
+            || Allowed::contains(call)
+            || !Pallets::is_migrating(metadata.pallet_name)
+    }
+}
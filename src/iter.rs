@@ -0,0 +1,112 @@
+//! Helpers to write steps iterating over a storage map.
+
+use crate::IsFinished;
+use frame_support::{
+    pallet_prelude::Weight,
+    storage::{PrefixIterator, PrefixIteratorOnRemoval},
+};
+use sp_std::prelude::*;
+
+/// Yields the entries of a storage map iterator as long as `weight_left` covers their weight,
+/// keeping track of the raw key of the last entry yielded so that the step can resume after it.
+///
+/// The weight of each entry, as given by `weight_of`, is charged to `weight_left` when the entry
+/// is yielded. Once an entry doesn't fit, the iteration stops for good, and the entry is yielded
+/// again when resuming from [`WeightBoundedIter::last_key`].
+///
+/// ```ignore
+/// fn step(&mut self) -> (IsFinished, Weight) {
+///     let max_weight = T::WeightInfo::v2_batch();
+///     let mut weight_left = max_weight;
+///     let iter = match self.last_key.take() {
+///         Some(last_key) => v1::Ledger::<T>::iter_from(last_key),
+///         None => v1::Ledger::<T>::iter(),
+///     };
+///     let mut iter = WeightBoundedIter::new(iter, &mut weight_left, |_| T::WeightInfo::v2_step());
+///     for (account, ledger) in &mut iter {
+///         Ledger::<T>::insert(account, Ledger::from(ledger));
+///     }
+///     self.last_key = Some(iter.last_key().to_vec());
+///     let finished = iter.finished();
+///     (finished, max_weight.saturating_sub(weight_left))
+/// }
+/// ```
+pub struct WeightBoundedIter<'a, T, OnRemoval, F> {
+    inner: PrefixIterator<T, OnRemoval>,
+    weight_left: &'a mut Weight,
+    weight_of: F,
+    last_key: Vec<u8>,
+    exhausted: bool,
+    stopped: bool,
+}
+
+impl<'a, T, OnRemoval, F> WeightBoundedIter<'a, T, OnRemoval, F>
+where
+    OnRemoval: PrefixIteratorOnRemoval,
+    F: FnMut(&T) -> Weight,
+{
+    /// Wraps `inner`, charging the weight of every entry to `weight_left`.
+    pub fn new(
+        inner: PrefixIterator<T, OnRemoval>,
+        weight_left: &'a mut Weight,
+        weight_of: F,
+    ) -> Self {
+        Self {
+            last_key: inner.last_raw_key().to_vec(),
+            inner,
+            weight_left,
+            weight_of,
+            exhausted: false,
+            stopped: false,
+        }
+    }
+
+    /// Returns the raw key of the last entry yielded, to resume from with `iter_from`.
+    ///
+    /// If no entry was yielded, it is the key the iteration started from, i.e. the map prefix or
+    /// the key given to `iter_from`.
+    pub fn last_key(&self) -> &[u8] {
+        &self.last_key
+    }
+
+    /// Whether every entry of the map has been yielded.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Returns [`IsFinished::Yes`] if every entry of the map has been yielded.
+    pub fn finished(&self) -> IsFinished {
+        if self.exhausted {
+            IsFinished::Yes
+        } else {
+            IsFinished::No
+        }
+    }
+}
+
+impl<'a, T, OnRemoval, F> Iterator for WeightBoundedIter<'a, T, OnRemoval, F>
+where
+    OnRemoval: PrefixIteratorOnRemoval,
+    F: FnMut(&T) -> Weight,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.stopped {
+            return None;
+        }
+        let Some(item) = self.inner.next() else {
+            self.exhausted = true;
+            self.stopped = true;
+            return None;
+        };
+        let weight = (self.weight_of)(&item);
+        if self.weight_left.any_lt(weight) {
+            self.stopped = true;
+            return None;
+        }
+        self.weight_left.saturating_reduce(weight);
+        self.last_key = self.inner.last_raw_key().to_vec();
+        Some(item)
+    }
+}
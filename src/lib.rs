@@ -40,8 +40,26 @@
 //!
 //! While the migratable is in progress, all dispatchables except `migrate`, are blocked, and returns
 //! a `MigrationInProgress` error.
+//!
+//! The calls of the pallets being migrated can be filtered out at the runtime level with
+//! [`filter::MigrationCallFilter`], which lets through the calls that must remain available.
 
-pub use migratable_procedural::{config, hooks, pallet};
+pub use migratable_procedural::{config, hooks, pallet, MapMigration};
+pub mod budget;
+#[cfg(any(feature = "std", feature = "try-runtime"))]
+pub mod debug;
+pub mod executor;
+pub mod filter;
+pub mod iter;
+pub mod prelude;
+pub mod runtime_api;
+#[cfg(feature = "std")]
+pub mod simulation;
+pub mod storage;
+#[cfg(feature = "std")]
+pub mod testing;
+#[cfg(feature = "try-runtime")]
+pub mod try_runtime;
 pub mod weights;
 
 extern crate alloc;
@@ -49,73 +67,590 @@ extern crate alloc;
 use frame_support::{
     pallet_prelude::{BoundedVec, Encode, MaxEncodedLen, StorageVersion, Weight},
     traits::ConstU32,
+    weights::RuntimeDbWeight,
 };
 pub use log;
 use parity_scale_codec::{Codec, Decode};
-use sp_runtime::Saturating;
+use scale_info::TypeInfo;
 #[cfg(feature = "try-runtime")]
 use sp_runtime::TryRuntimeError;
-#[cfg(feature = "try-runtime")]
+use sp_runtime::{DispatchError, RuntimeDebug, Saturating, TransactionOutcome};
 use sp_std::prelude::*;
 
-const PROOF_ENCODE: &str = "Tuple::max_encoded_len() < Cursor::max_encoded_len()` is verified in `Self::integrity_test()`; qed";
+const PROOF_ENCODE: &str =
+    "`Tuple::MAX_CURSOR_LEN < Cursor::bound()` is verified in `Self::integrity_test()`; qed";
 const PROOF_DECODE: &str =
     "We encode to the same type in this trait only. No other code touches this item; qed";
 
+/// Returns the raw number of a [`StorageVersion`].
+pub fn storage_version_number(version: StorageVersion) -> u16 {
+    version.using_encoded(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+/// Returns the version following `version`, or `None` if `version` is the last possible one.
+pub fn next_version(version: StorageVersion) -> Option<StorageVersion> {
+    storage_version_number(version)
+        .checked_add(1)
+        .map(StorageVersion::new)
+}
+
+/// Returns the weight of completing a version within `migrate`: storing the new storage version,
+/// recording the duration of the completed version and building the cursor of the next one, whose
+/// write is already part of the `migrate` overhead.
+pub fn version_advance_weight(db_weight: RuntimeDbWeight) -> Weight {
+    db_weight.reads_writes(2, 3)
+}
+
+/// Runs `f` with `remaining_weight` minus `reserved`, capped at `remaining_weight`, and gives the
+/// reservation back afterwards, returning the weight consumed by `f`.
+///
+/// Used by the `hooks` macro to keep the `reserve_for_user` weight of the user's own `on_idle`
+/// out of the reach of the migrations.
+pub fn with_reserved_weight(
+    remaining_weight: &mut Weight,
+    reserved: Weight,
+    f: impl FnOnce(&mut Weight) -> Weight,
+) -> Weight {
+    let reserved = reserved.min(*remaining_weight);
+    remaining_weight.saturating_reduce(reserved);
+    let weight = f(remaining_weight);
+    remaining_weight.saturating_accrue(reserved);
+    weight
+}
+
+/// Runs the steps of `version` like [`MigrateSequence::steps_checked`], keeping `reserved` out of
+/// the budget of the steps, so that it is still in `weight_left` for the work done once the
+/// version completes.
+pub fn steps_with_reserve<S: MigrateSequence>(
+    version: StorageVersion,
+    cursor: &[u8],
+    weight_left: &mut Weight,
+    steps_in_block: u32,
+    reserved: Weight,
+    checkpoint: &mut dyn FnMut(Cursor),
+) -> Result<StepResult, MigrateError> {
+    let reserved = reserved.min(*weight_left);
+    reduce_weight(weight_left, reserved);
+    let result = S::steps_checked(version, cursor, weight_left, steps_in_block, checkpoint);
+    add_weight(weight_left, reserved);
+    result
+}
+
+/// Reduces `weight` by `by`, saturating at zero like `saturating_reduce`.
+///
+/// With `debug_assertions`, it asserts that `weight` didn't saturate, so that tests catch a
+/// mis-estimated weight that would otherwise be masked, e.g. a step consuming more than its
+/// `max_step_weight`.
+#[doc(hidden)]
+pub fn reduce_weight(weight: &mut Weight, by: Weight) {
+    debug_assert!(weight.all_gte(by), "Weight underflow: {weight:?} reduced by {by:?}");
+    weight.saturating_reduce(by);
+}
+
+/// Adds `by` to `weight`, saturating at the maximum like `saturating_accrue`.
+///
+/// With `debug_assertions`, it asserts that `weight` didn't saturate, see [`reduce_weight`].
+#[doc(hidden)]
+pub fn add_weight(weight: &mut Weight, by: Weight) {
+    debug_assert!(weight.checked_add(&by).is_some(), "Weight overflow: {weight:?} added {by:?}");
+    weight.saturating_accrue(by);
+}
+
+/// Charges `weight` to `weight_left`, adding the part that doesn't fit into it to `overrun`.
+///
+/// Used by `migrate` for the work whose weight is only known once it is done, e.g. starting the
+/// next version, so that it is taken out of the weight limit whenever it fits.
+#[doc(hidden)]
+pub fn charge_weight(weight_left: &mut Weight, overrun: &mut Weight, weight: Weight) {
+    add_weight(overrun, weight.saturating_sub(*weight_left));
+    weight_left.saturating_reduce(weight);
+}
+
+/// Returns the weight consumed out of `weight_limit`, given the `weight_left`.
+///
+/// With `debug_assertions`, it asserts that `weight_left` is within `weight_limit`, see
+/// [`reduce_weight`].
+#[doc(hidden)]
+pub fn consumed_weight(weight_limit: Weight, weight_left: Weight) -> Weight {
+    let mut weight = weight_limit;
+    reduce_weight(&mut weight, weight_left);
+    weight
+}
+
+/// Formats a [`Weight`] in the log lines of the migrations.
+///
+/// With `std`, it is the `Display` format of [`Weight`]. Without it, the `ref_time` and
+/// `proof_size` are written as two plain integers, so that the runtime doesn't pull in the
+/// formatting machinery of [`Weight`].
+#[doc(hidden)]
+pub struct LogWeight(pub Weight);
+
+impl core::fmt::Display for LogWeight {
+    #[cfg(feature = "std")]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.0, f)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}/{}", self.0.ref_time(), self.0.proof_size())
+    }
+}
+
+/// Whether there is enough weight left to run another step weighing at most `max_step_weight`.
+///
+/// A step is allowed when the weight left exactly matches `max_step_weight`, as it is an upper
+/// bound of what the step can consume.
+fn can_step(weight_left: &Weight, max_step_weight: Weight) -> bool {
+    weight_left.all_gte(max_step_weight)
+}
+
+/// Returns the maximum weight of a step of `M`, as given by its deprecated
+/// [`MigrationStep::max_step_weight`], which the steps whose weight is only known at runtime still
+/// override.
+fn step_weight<M: MigrationStep>() -> Weight {
+    #[allow(deprecated)]
+    M::max_step_weight()
+}
+
 fn invalid_version(version: StorageVersion) -> ! {
     panic!("Required migratable {version:?} not supported by this runtime. This is a bug.");
 }
 
+/// Returns the length of the header [`encode_cursor`] prefixes the encoded step with.
+fn cursor_header_len<M: MigrationStep>() -> usize {
+    if M::TAG_CURSOR {
+        3
+    } else {
+        1
+    }
+}
+
+/// Encodes `migration` into a [`Cursor`], prefixed with its [`MigrationStep::VERSION`] (unless
+/// [`MigrationStep::TAG_CURSOR`] is disabled) and its [`MigrationStep::CURSOR_SCHEMA`].
+fn encode_cursor<M: MigrationStep>(migration: &M) -> Cursor {
+    try_encode_cursor(migration).expect(PROOF_ENCODE)
+}
+
+/// Same as [`encode_cursor`], but returns an error instead of panicking if the encoded step
+/// doesn't fit into a [`Cursor`].
+///
+/// The step is encoded in place into a buffer sized from its [`Encode::size_hint`], which then
+/// becomes the cursor without being copied.
+fn try_encode_cursor<M: MigrationStep>(migration: &M) -> Result<Cursor, MigrateError> {
+    let mut cursor =
+        alloc::vec::Vec::with_capacity(cursor_header_len::<M>() + migration.size_hint());
+    if M::TAG_CURSOR {
+        cursor.extend_from_slice(&M::VERSION.to_le_bytes());
+    }
+    cursor.push(M::CURSOR_SCHEMA);
+    migration.encode_to(&mut cursor);
+    cursor.try_into().map_err(|_| MigrateError::CursorOverflow)
+}
+
+/// Passes the cursor of `step` to `checkpoint`, skipping the checkpoint if the step can't be
+/// encoded, as the last persisted cursor is still valid.
+fn checkpoint_step<M: MigrationStep>(step: &M, checkpoint: &mut dyn FnMut(Cursor)) {
+    match try_encode_cursor(step) {
+        Ok(cursor) => checkpoint(cursor),
+        Err(error) => frame_support::defensive!("Failed to encode the checkpointed step", error),
+    }
+}
+
+/// Decodes a cursor produced by [`encode_cursor`], upgrading it through
+/// [`MigrationStep::migrate_cursor`] if it was persisted with a different schema.
+fn try_decode_cursor<M: MigrationStep>(mut cursor: &[u8]) -> Result<M, MigrateError> {
+    if M::TAG_CURSOR {
+        let version = u16::decode(&mut cursor).map_err(|_| MigrateError::CursorDecode)?;
+        if version != M::VERSION {
+            return Err(MigrateError::VersionMismatch(version));
+        }
+    }
+    let (schema, mut bytes) = cursor.split_first().ok_or(MigrateError::CursorDecode)?;
+    let migration = if *schema == M::CURSOR_SCHEMA {
+        <M as Decode>::decode(&mut bytes)
+    } else {
+        M::migrate_cursor(*schema, bytes)
+    };
+    migration.map_err(|_| MigrateError::CursorDecode)
+}
+
+/// Same as [`try_decode_cursor`], for cursors known to be valid.
+fn decode_cursor<M: MigrationStep>(cursor: &[u8]) -> M {
+    try_decode_cursor(cursor).expect(PROOF_DECODE)
+}
+
+/// Runs the steps of `migration` until it is finished or the weight limit is reached, passing the
+/// intermediate cursors checkpointed by the steps to `checkpoint`.
+///
+/// `steps_in_block` steps of `migration` already ran in the current block, and count towards its
+/// [`MigrationStep::max_steps_per_block`].
+///
+/// Returns [`MigrateError::CursorOverflow`] if the step doesn't fit into a [`Cursor`] once it has to be
+/// persisted, or [`MigrateError::CursorDecode`] if a failed step can't be restored.
+fn run_steps<M: MigrationStep>(
+    mut migration: M,
+    weight_left: &mut Weight,
+    steps_in_block: u32,
+    checkpoint: &mut dyn FnMut(Cursor),
+) -> Result<StepResult, MigrateError> {
+    let max_weight = step_weight::<M>();
+    let max_steps = M::max_steps_per_block();
+    let mut steps_done: u32 = 0;
+    while can_step(weight_left, max_weight) {
+        if max_steps.is_some_and(|max_steps| steps_in_block.saturating_add(steps_done) >= max_steps)
+        {
+            return Ok(StepResult::Yielded {
+                cursor: try_encode_cursor(&migration)?,
+                steps_done,
+            });
+        }
+        let (finished, weight) = match run_step(&mut migration, checkpoint)? {
+            Ok(result) => result,
+            Err(weight) => {
+                reduce_weight(weight_left, weight);
+                return Ok(StepResult::Failed {
+                    cursor: try_encode_cursor(&migration)?,
+                    steps_done,
+                });
+            }
+        };
+        reduce_weight(weight_left, weight);
+        let completed = match finished {
+            IsFinished::Yes => true,
+            IsFinished::No => false,
+            IsFinished::Deferred => break,
+        };
+        steps_done.saturating_accrue(1);
+        // not bounded by `max_weight`, so it may exceed the weight left after a step consuming
+        // its whole `max_step_weight`
+        weight_left.saturating_reduce(migration.post_step(steps_done));
+        if completed {
+            return Ok(StepResult::Completed { steps_done });
+        }
+    }
+    Ok(StepResult::InProgress {
+        cursor: try_encode_cursor(&migration)?,
+        steps_done,
+    })
+}
+
+/// Runs a single step of `migration`, within a transaction if it is
+/// [`MigrationStep::TRANSACTIONAL`].
+///
+/// Returns the weight consumed as the inner error if the transactional step failed, once its writes
+/// are rolled back and `migration` is restored to the state it had before the step.
+///
+/// Fails with [`MigrateError::CursorDecode`] if `migration` can't be restored, as it is left with
+/// the changes of the rolled back step, which must not be persisted.
+fn run_step<M: MigrationStep>(
+    migration: &mut M,
+    checkpoint: &mut dyn FnMut(Cursor),
+) -> Result<Result<(IsFinished, Weight), Weight>, MigrateError> {
+    if !M::TRANSACTIONAL {
+        return Ok(Ok(
+            migration.step_with_checkpoint(&mut |step: &M| checkpoint_step(step, checkpoint))
+        ));
+    }
+    let snapshot = migration.encode();
+    // left as is if the transaction can't be opened, e.g. when nested too deep
+    let mut outcome = Err(Weight::zero());
+    let _ = frame_support::storage::with_transaction(|| {
+        outcome = migration.try_step();
+        if outcome.is_ok() {
+            TransactionOutcome::Commit(Ok(()))
+        } else {
+            TransactionOutcome::Rollback(Err(DispatchError::from(MigrateError::StepFailed)))
+        }
+    });
+    if outcome.is_err() {
+        // restore the state the step had before the rolled back writes
+        match M::decode(&mut &snapshot[..]) {
+            Ok(restored) => *migration = restored,
+            Err(error) => {
+                frame_support::defensive!("Failed to restore the rolled back step", error);
+                return Err(MigrateError::CursorDecode);
+            }
+        }
+    }
+    Ok(outcome)
+}
+
 /// The cursor used to encode the position (usually the last iterated key) of the current migratable
 /// step.
 pub type Cursor = BoundedVec<u8, ConstU32<1024>>;
 
 /// IsFinished describes whether a migratable is finished or not.
 pub enum IsFinished {
+    /// The migratable is finished.
     Yes,
+    /// More work remains, and the next step can be run right away.
     No,
+    /// More work remains, but the step can't progress until a later block, e.g. because it
+    /// depends on data produced by another pallet.
+    ///
+    /// The deferred step is not counted in `steps_done`, and no further steps are run in the
+    /// current call, so the migration is resumed in the next block.
+    Deferred,
 }
 
 /// A trait that allows to migrate storage from one version to another.
 ///
 /// The migratable is done in steps. The migratable is finished when
 /// `step()` returns `IsFinished::Yes`.
-pub trait MigrationStep: Codec + MaxEncodedLen + Default {
+pub trait MigrationStep: Codec + Default {
     /// Returns the version of the migratable.
+    ///
+    /// Versions are `u16`, like the [`StorageVersion`] they are stored as, so 65535 is the last
+    /// version a pallet can migrate to. A sequence going past it fails to compile.
     const VERSION: u16;
 
+    /// The maximum length of the encoded step, which must fit into the [`Cursor`].
+    ///
+    /// It must be declared by hand, as `MaxEncodedLen::max_encoded_len()` is not a `const fn`.
+    /// Steps implementing [`MaxEncodedLen`] should also override
+    /// [`MigrationStep::encoded_len_bound`], so that the declared length is checked against
+    /// it, while steps holding unbounded collections must declare their own worst case.
+    const MAX_CURSOR_LEN: u32;
+
+    /// The schema of the encoded step, stored as the leading byte of the [`Cursor`].
+    ///
+    /// Bump it whenever the encoding of the step changes while a migration may be in flight, and
+    /// implement [`MigrationStep::migrate_cursor`] to upgrade the persisted cursor.
+    const CURSOR_SCHEMA: u8 = 0;
+
+    /// Whether the [`Cursor`] is prefixed with [`MigrationStep::VERSION`], so that a cursor left
+    /// over from another version is detected instead of being decoded as this step.
+    ///
+    /// The tag takes two bytes of the cursor, and can be disabled for steps tight on space.
+    const TAG_CURSOR: bool = true;
+
+    /// The maximum weight that can be consumed in a single step, as a constant that tooling can
+    /// read at compile time. Preferred over overriding [`MigrationStep::max_step_weight`].
+    ///
+    /// Steps whose weight is only known at runtime, e.g. when derived from the `DbWeight` of the
+    /// runtime, leave it to `None` and override [`MigrationStep::max_step_weight`] instead.
+    const MAX_STEP_WEIGHT: Option<Weight> = None;
+
     /// Returns the maximum weight that can be consumed in a single step.
-    fn max_step_weight() -> Weight;
+    ///
+    /// Defaults to [`MigrationStep::MAX_STEP_WEIGHT`]. Without either of them, it is
+    /// `Weight::MAX`, so that the step never runs, which [`MigrationStep::integrity_check`]
+    /// reports as [`IntegrityError::MissingMaxStepWeight`].
+    #[deprecated(
+        note = "Define `MAX_STEP_WEIGHT` instead. Only the steps whose weight is known at runtime \
+                should override it."
+    )]
+    fn max_step_weight() -> Weight {
+        Self::MAX_STEP_WEIGHT.unwrap_or(Weight::MAX)
+    }
 
     /// Process one step of the migratable.
     ///
     /// Returns whether the migratable is finished and the weight consumed.
     fn step(&mut self) -> (IsFinished, Weight);
 
-    /// Verify that the migratable step fits into `Cursor`, and that `max_step_weight` is not greater
-    /// than `max_block_weight`.
-    fn integrity_test(max_block_weight: Weight) {
-        if Self::max_step_weight().any_gt(max_block_weight) {
-            panic!(
-                "Invalid max_step_weight for Migration {}. Value should be lower than {}",
-                Self::VERSION,
-                max_block_weight
-            );
+    /// Same as [`MigrationStep::step`], for steps processing large batches, which can call
+    /// `checkpoint` with their intermediate state to resume from if the batch is interrupted.
+    ///
+    /// The last checkpoint is only persisted, as the cursor, if the `migrate` call fails after it,
+    /// e.g. because the final state of the step doesn't fit into the [`Cursor`]. A panic discards
+    /// the whole block, checkpoints included, and the cursor of a successful call supersedes them.
+    ///
+    /// The weight of every checkpoint, an encoding of the step, must be included in the weight
+    /// returned. The default implementation never checkpoints.
+    fn step_with_checkpoint(&mut self, _checkpoint: &mut dyn FnMut(&Self)) -> (IsFinished, Weight) {
+        self.step()
+    }
+
+    /// Returns a human readable name of the migratable, used to describe it to off-chain tools.
+    ///
+    /// Defaults to the name of the type.
+    fn name() -> &'static str {
+        core::any::type_name::<Self>()
+    }
+
+    /// Called after every step that is not deferred, with the number of steps done so far in the
+    /// current block, e.g. to update a progress counter or emit an event every N steps.
+    ///
+    /// Returns the weight consumed, which is charged apart from [`MigrationStep::max_step_weight`].
+    fn post_step(&self, _steps_done: u32) -> Weight {
+        Weight::zero()
+    }
+
+    /// Called once on the default step when the migration of this version begins, i.e. when its
+    /// cursor is created, before the first step. The changes to `self` are part of that cursor,
+    /// e.g. to record the total count of items to migrate for progress tracking.
+    ///
+    /// Returns the weight consumed.
+    fn on_version_start(&mut self) -> Weight {
+        Weight::zero()
+    }
+
+    /// Checks that the migratable can start, e.g. that the other pallets it depends on are
+    /// already at a given storage version.
+    ///
+    /// Called before the first step of the migratable, which is retried later on failure.
+    fn check_dependencies() -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    /// Checks the invariants of the migrated storage, without the state of the pre-checks.
+    ///
+    /// Unlike [`MigrationStep::post_upgrade_step`], it is available without `try-runtime`, so that
+    /// a completed migration can be audited on-chain, e.g. through the generated `verify_migration`
+    /// dispatchable.
+    fn verify() -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    /// Whether to skip this migratable when all the migrations are run within the runtime upgrade
+    /// under `try-runtime`, e.g. for a step iterating a huge map, which is then tested on its own.
+    ///
+    /// A skipped version is marked as migrated without running its steps nor its checks.
+    #[cfg(feature = "try-runtime")]
+    const SKIP_IN_TEST_ALL: bool = false;
+
+    /// Caps the number of steps run in a single block, regardless of the weight left, e.g. for
+    /// steps depositing many events. `None` runs as many steps as the weight allows.
+    ///
+    /// The steps of every `migrate` call of the block count, including the `migrate` dispatchable.
+    ///
+    /// Once reached, the migration resumes in the next block.
+    fn max_steps_per_block() -> Option<u32> {
+        None
+    }
+
+    /// The maximum length of the encoded step known at runtime, e.g. `Some(Self::max_encoded_len())`
+    /// for steps implementing [`MaxEncodedLen`]. [`MigrationStep::integrity_check`] fails if
+    /// [`MigrationStep::MAX_CURSOR_LEN`] is lower.
+    fn encoded_len_bound() -> Option<usize> {
+        None
+    }
+
+    /// Whether every step runs within a storage transaction, for steps performing several writes
+    /// that must be applied together.
+    ///
+    /// Transactional steps run [`MigrationStep::try_step`] instead of
+    /// [`MigrationStep::step_with_checkpoint`]. Disabled by default, as transactions add some
+    /// overhead.
+    const TRANSACTIONAL: bool = false;
+
+    /// Whether the migratable does nothing, like a [`NoopMigration`], so that it can be completed
+    /// right after the previous version, within the same `migrate` call.
+    const IS_NOOP: bool = false;
+
+    /// Same as [`MigrationStep::step`], for [`MigrationStep::TRANSACTIONAL`] steps, which can fail
+    /// with the weight consumed.
+    ///
+    /// The writes of a failed step are rolled back and the step is restored to its state before
+    /// the call, to be retried in the next block. The migration is halted once
+    /// `Config::MAX_STEP_FAILURES` steps failed in a row. Defaults to [`MigrationStep::step`].
+    fn try_step(&mut self) -> Result<(IsFinished, Weight), Weight> {
+        Ok(self.step())
+    }
+
+    /// Describes what the next step would process, e.g. the next key, to preview the migration
+    /// without running it. Defaults to an empty description.
+    fn describe_position(&self) -> alloc::string::String {
+        alloc::string::String::new()
+    }
+
+    /// Estimates the number of items left to migrate, e.g. by scanning the remaining key range,
+    /// to show the progress within the version. Defaults to `None`, i.e. unknown.
+    fn estimate_remaining_items(&self) -> Option<u64> {
+        None
+    }
+
+    /// Whether this migratable applies to the runtime, e.g. a backfill that is only needed on a
+    /// testnet.
+    ///
+    /// When it returns `false`, the version is completed right away, like a [`NoopMigration`],
+    /// with no step done, and neither its cursor nor its `try-runtime` checks are run.
+    fn should_run() -> bool {
+        true
+    }
+
+    /// Rebuild the step from a cursor persisted with `old_schema`, which differs from
+    /// [`MigrationStep::CURSOR_SCHEMA`].
+    ///
+    /// The default implementation assumes the encoding did not change and decodes `bytes` as is.
+    fn migrate_cursor(
+        _old_schema: u8,
+        mut bytes: &[u8],
+    ) -> Result<Self, parity_scale_codec::Error> {
+        <Self as Decode>::decode(&mut bytes)
+    }
+
+    /// Verify that the migratable step fits into `max_cursor_len` bytes, and that `max_step_weight`
+    /// is not greater than `max_block_weight`, returning every failed check.
+    ///
+    /// A step whose cursor fits but takes more than 90% of `max_cursor_len` is logged as a warning,
+    /// as it is close to overflowing the cursor.
+    fn integrity_check(max_block_weight: Weight, max_cursor_len: usize) -> Vec<IntegrityError> {
+        let mut errors = Vec::new();
+        let max_step_weight = step_weight::<Self>();
+        if Self::MAX_STEP_WEIGHT.is_none() && max_step_weight == Weight::MAX {
+            errors.push(IntegrityError::MissingMaxStepWeight {
+                version: Self::VERSION,
+            });
+        } else {
+            if max_step_weight.ref_time() > max_block_weight.ref_time() {
+                errors.push(IntegrityError::RefTimeTooHigh {
+                    version: Self::VERSION,
+                    ref_time: max_step_weight.ref_time(),
+                    max: max_block_weight.ref_time(),
+                });
+            }
+            // checked on its own, as the PoV is usually the binding constraint on parachains
+            if max_step_weight.proof_size() > max_block_weight.proof_size() {
+                errors.push(IntegrityError::ProofSizeTooHigh {
+                    version: Self::VERSION,
+                    proof_size: max_step_weight.proof_size(),
+                    max: max_block_weight.proof_size(),
+                });
+            }
         }
 
-        let len = <Self as MaxEncodedLen>::max_encoded_len();
-        let max = Cursor::bound();
-        if len > max {
-            panic!(
-                "Migration {} has size {} which is bigger than the maximum of {}",
-                Self::VERSION,
+        if let Some(bound) = Self::encoded_len_bound() {
+            if bound > Self::MAX_CURSOR_LEN as usize {
+                errors.push(IntegrityError::CursorLenTooLow {
+                    version: Self::VERSION,
+                    declared: Self::MAX_CURSOR_LEN,
+                    bound,
+                });
+            }
+        }
+
+        // account for the header of the cursor
+        let len = (Self::MAX_CURSOR_LEN as usize).saturating_add(cursor_header_len::<Self>());
+        if len > max_cursor_len {
+            errors.push(IntegrityError::CursorTooLong {
+                version: Self::VERSION,
                 len,
-                max,
+                max: max_cursor_len,
+            });
+        } else if len.saturating_mul(10) > max_cursor_len.saturating_mul(9) {
+            // little headroom is left for a future change of the step
+            #[cfg(any(feature = "std", feature = "try-runtime"))]
+            log::warn!(
+                target: "migratable",
+                "The cursor of migration {} takes {len} bytes, more than 90% of the {max_cursor_len} \
+                 bytes available",
+                Self::VERSION,
             );
         }
+        errors
+    }
+
+    /// Same as [`MigrationStep::integrity_check`], panicking if any check failed.
+    fn integrity_test(max_block_weight: Weight, max_cursor_len: usize) {
+        assert_integrity(Self::integrity_check(max_block_weight, max_cursor_len));
     }
 
     /// Execute some pre-checks prior to running the first step of this migratable.
+    ///
+    /// See [`try_runtime::TypedUpgradeChecks`] to pass a typed state to the post-checks.
     #[cfg(feature = "try-runtime")]
     fn pre_upgrade_step() -> Result<Vec<u8>, TryRuntimeError> {
         Ok(Vec::new())
@@ -126,6 +661,28 @@ pub trait MigrationStep: Codec + MaxEncodedLen + Default {
     fn post_upgrade_step(_state: Vec<u8>) -> Result<(), TryRuntimeError> {
         Ok(())
     }
+
+    /// Same as [`MigrationStep::pre_upgrade_step`], also returning the weight consumed by the
+    /// checks, so that it is accounted for in the weight reported by `try-runtime`.
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade_step_with_weight() -> Result<(Vec<u8>, Weight), TryRuntimeError> {
+        Self::pre_upgrade_step().map(|state| (state, Weight::zero()))
+    }
+
+    /// Same as [`MigrationStep::post_upgrade_step`], also returning the weight consumed by the
+    /// checks, so that it is accounted for in the weight reported by `try-runtime`.
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade_step_with_weight(state: Vec<u8>) -> Result<Weight, TryRuntimeError> {
+        Self::post_upgrade_step(state).map(|_| Weight::zero())
+    }
+
+    /// Returns a human readable description of the state of the step, used by
+    /// [`debug::describe_cursor_for`] to pretty-print the cursor, e.g. `Some(format!("{self:?}"))`
+    /// for steps implementing `Debug`.
+    #[cfg(any(feature = "std", feature = "try-runtime"))]
+    fn debug_description(&self) -> Option<alloc::string::String> {
+        None
+    }
 }
 
 /// A noop migratable that can be used when there is no migratable to be done for a given version.
@@ -135,121 +692,1287 @@ pub struct NoopMigration<const N: u16>;
 
 impl<const N: u16> MigrationStep for NoopMigration<N> {
     const VERSION: u16 = N;
-    fn max_step_weight() -> Weight {
-        Weight::zero()
-    }
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    const IS_NOOP: bool = true;
     fn step(&mut self) -> (IsFinished, Weight) {
         (IsFinished::Yes, Weight::zero())
     }
 }
 
-mod private {
-    use crate::MigrationStep;
+/// A migratable that only bumps the storage version in a step of its own, unlike the
+/// [`NoopMigration`] completed along with the version before it.
+///
+/// The step does nothing: the write of the storage version is charged as the advance of every
+/// version, see [`version_advance_weight`].
+#[derive(frame_support::DefaultNoBound, Encode, Decode, MaxEncodedLen)]
+#[codec(encode_bound(), decode_bound(), mel_bound())]
+pub struct VersionBumpMigration<const N: u16, T>(core::marker::PhantomData<T>);
 
-    pub trait Sealed {}
+impl<const N: u16, T: frame_system::Config> MigrationStep for VersionBumpMigration<N, T> {
+    const VERSION: u16 = N;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+}
 
-    #[impl_trait_for_tuples::impl_for_tuples(10)]
-    #[tuple_types_custom_trait_bound(MigrationStep)]
-    impl Sealed for Tuple {}
+/// A migratable completed in a single step, for [`OneShotMigration`].
+pub trait OneShot {
+    /// Runs the whole migratable, returning the weight consumed.
+    fn migrate() -> Weight;
+
+    /// Returns the maximum weight consumed by [`OneShot::migrate`].
+    fn max_weight() -> Weight;
 }
 
-/// Defines a sequence of migrations.
+/// Runs the [`OneShot`] migratable `M` as version `V` in a single step, without a cursor.
 ///
-/// The sequence must be defined by a tuple of migrations, each of which must implement the
-/// `MigrationStep` trait. Migrations must be ordered by their versions with no gaps.
-pub trait MigrateSequence: private::Sealed {
-    /// Returns the range of versions that this migrations sequence can handle.
-    /// Migrations must be ordered by their versions with no gaps.
-    const VERSION_RANGE: (u16, u16);
+/// ```ignore
+/// pub struct RenameOwner<T>(PhantomData<T>);
+///
+/// impl<T: Config> migratable::OneShot for RenameOwner<T> {
+///     fn migrate() -> Weight {
+///         if let Some(owner) = v3::Admin::<T>::take() {
+///             Owner::<T>::put(owner);
+///         }
+///         Self::max_weight()
+///     }
+///
+///     fn max_weight() -> Weight {
+///         T::DbWeight::get().reads_writes(1, 2)
+///     }
+/// }
+///
+/// type Migrations = (v3::Migration<T>, OneShotMigration<4, RenameOwner<T>>);
+/// ```
+#[derive(frame_support::DefaultNoBound, Encode, Decode, MaxEncodedLen)]
+#[codec(encode_bound(), decode_bound(), mel_bound())]
+pub struct OneShotMigration<const V: u16, M>(core::marker::PhantomData<M>);
+
+impl<const V: u16, M: OneShot> MigrationStep for OneShotMigration<V, M> {
+    const VERSION: u16 = V;
+    const MAX_CURSOR_LEN: u32 = 0;
+    fn max_step_weight() -> Weight {
+        M::max_weight()
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, M::migrate())
+    }
+    fn name() -> &'static str {
+        core::any::type_name::<M>()
+    }
+}
 
-    /// Returns the default cursor for the given version.
-    fn new(version: StorageVersion) -> Cursor;
+/// Runs the single-block migration `M` as version `V`, within a single step, so that existing
+/// [`OnRuntimeUpgrade`](frame_support::traits::OnRuntimeUpgrade) migrations can be folded into a
+/// sequence, and only the heavy ones rewritten as multi-block steps.
+///
+/// The weight of `M` is only known once it ran, so the step is weighed as `MaxWeight`, an upper
+/// bound of it. A whole block, i.e. its `max_block`, would be the safest bound, but it never fits
+/// into the weight left to `on_idle`, so the step would never run. The `try-runtime` checks of the
+/// step are forwarded to `M`.
+///
+/// ```ignore
+/// parameter_types! {
+///     pub MigrateToV2Weight: Weight = Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 10, 256 * 1024);
+/// }
+///
+/// type Migrations = (
+///     FromOnRuntimeUpgrade<2, v2::MigrateToV2<T>, MigrateToV2Weight>,
+///     v3::Migration<T>,
+/// );
+/// ```
+#[derive(frame_support::DefaultNoBound, Encode, Decode, MaxEncodedLen)]
+#[codec(encode_bound(), decode_bound(), mel_bound())]
+pub struct FromOnRuntimeUpgrade<const V: u16, M, MaxWeight>(
+    core::marker::PhantomData<(M, MaxWeight)>,
+);
 
+impl<const V: u16, M, MaxWeight> MigrationStep for FromOnRuntimeUpgrade<V, M, MaxWeight>
+where
+    M: frame_support::traits::OnRuntimeUpgrade,
+    MaxWeight: frame_support::traits::Get<Weight>,
+{
+    const VERSION: u16 = V;
+    const MAX_CURSOR_LEN: u32 = 0;
+    fn max_step_weight() -> Weight {
+        MaxWeight::get()
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, M::on_runtime_upgrade())
+    }
+    fn name() -> &'static str {
+        core::any::type_name::<M>()
+    }
     #[cfg(feature = "try-runtime")]
-    fn pre_upgrade_step(_version: StorageVersion) -> Result<Vec<u8>, TryRuntimeError> {
-        Ok(Vec::new())
+    fn pre_upgrade_step() -> Result<Vec<u8>, TryRuntimeError> {
+        M::pre_upgrade()
     }
-
     #[cfg(feature = "try-runtime")]
-    fn post_upgrade_step(_version: StorageVersion, _state: Vec<u8>) -> Result<(), TryRuntimeError> {
-        Ok(())
+    fn post_upgrade_step(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+        M::post_upgrade(state)
     }
+}
 
-    /// Execute the migratable step until the weight limit is reached.
-    fn steps(version: StorageVersion, cursor: &[u8], weight_left: &mut Weight) -> StepResult;
+/// The phases of a [`PhasedMigration`], usually an enum with a variant per phase holding the
+/// cursor of that phase.
+pub trait MigrationPhases: Codec + Default {
+    /// The maximum length of any encoded phase, including its enum index.
+    const MAX_CURSOR_LEN: u32;
 
-    /// Verify that the migratable step fits into `Cursor`, and that `max_step_weight` is not greater
-    /// than `max_block_weight`.
-    fn integrity_test(max_block_weight: Weight);
+    /// Returns the maximum weight of a step of any phase.
+    fn max_step_weight() -> Weight;
 
-    /// Returns whether migrating from `in_storage` to `target` is supported.
-    ///
-    /// A migratable is supported if `VERSION_RANGE` is (in_storage + 1, target).
-    fn is_upgrade_supported(in_storage: StorageVersion, target: StorageVersion) -> bool {
-        let (low, high) = Self::VERSION_RANGE;
-        target == high && in_storage + 1 == low
+    /// Runs a step of the current phase, returning whether the phase is finished and the weight
+    /// consumed.
+    fn step(&mut self) -> (IsFinished, Weight);
+
+    /// Returns the phase following the current one once it is finished, or `None` if it was the
+    /// last phase.
+    fn next(&self) -> Option<Self>;
+}
+
+/// Runs the [`MigrationPhases`] `P` as version `V`, one phase after the other.
+///
+/// The cursor holds the phase in progress along with its own cursor. Moving on to the next phase
+/// happens within the step finishing the previous one, and the version is completed once the last
+/// phase is finished.
+///
+/// Generic phases derive `frame_support::DefaultNoBound` rather than `Default`, which would
+/// require the runtime to implement `Default`.
+///
+/// ```ignore
+/// #[derive(DefaultNoBound, Encode, Decode)]
+/// pub enum Phases<T: Config> {
+///     #[default]
+///     Copy,
+///     CopyFrom(Vec<u8>),
+///     DeleteOld,
+///     RebuildIndex(Option<T::AccountId>),
+/// }
+///
+/// impl<T: Config> migratable::MigrationPhases for Phases<T> {
+///     // -- snip --
+///
+///     fn next(&self) -> Option<Self> {
+///         match self {
+///             Self::Copy | Self::CopyFrom(_) => Some(Self::DeleteOld),
+///             Self::DeleteOld => Some(Self::RebuildIndex(None)),
+///             Self::RebuildIndex(_) => None,
+///         }
+///     }
+/// }
+///
+/// type Migrations = (v3::Migration<T>, PhasedMigration<4, v4::Phases<T>>);
+/// ```
+#[derive(Default, Encode, Decode)]
+pub struct PhasedMigration<const V: u16, P>(P);
+
+impl<const V: u16, P: MigrationPhases> MigrationStep for PhasedMigration<V, P> {
+    const VERSION: u16 = V;
+    const MAX_CURSOR_LEN: u32 = P::MAX_CURSOR_LEN;
+    fn max_step_weight() -> Weight {
+        P::max_step_weight()
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        let (finished, weight) = self.0.step();
+        match finished {
+            IsFinished::Yes => match self.0.next() {
+                Some(next) => {
+                    self.0 = next;
+                    (IsFinished::No, weight)
+                }
+                None => (IsFinished::Yes, weight),
+            },
+            finished => (finished, weight),
+        }
+    }
+    fn name() -> &'static str {
+        core::any::type_name::<P>()
     }
 }
 
-/// The result of running the migratable.
-#[derive(Debug, PartialEq)]
-pub enum MigrateResult {
-    /// No migratable was performed
-    NoMigrationPerformed,
-    /// No migratable currently in progress
-    NoMigrationInProgress,
-    /// A migratable is in progress
-    InProgress { steps_done: u32 },
-    /// All migrations are completed
-    Completed,
+/// Runs the steps `A` and `B` of two decoupled storage subsystems side by side, as a single
+/// version, instead of migrating one after the other.
+///
+/// Every step advances both `A` and `B` by one step, until both are finished, so the weight of a
+/// `migrate` call is split between them. The cursor holds the state of both, and the version is
+/// completed once both are finished. A part that is deferred doesn't hold the other one back,
+/// unless both are.
+///
+/// `A` and `B` must have the same [`MigrationStep::VERSION`]. The cursor holds each part with its
+/// own length, and packs their [`MigrationStep::CURSOR_SCHEMA`]s, which must be lower than 16,
+/// into its schema, so that each part upgrades its own state through
+/// [`MigrationStep::migrate_cursor`]. The checkpoints of a part are forwarded along with the state
+/// of the other one. The step is [`MigrationStep::TRANSACTIONAL`] if either part is, in which case
+/// a failed [`MigrationStep::try_step`] of either part rolls back both.
+///
+/// ```ignore
+/// type Migrations = (
+///     v2::Migration<T>,
+///     Parallel<v3::MigrateLedgers<T>, v3::MigrateNominations<T>>,
+/// );
+/// ```
+pub struct Parallel<A, B> {
+    a: Option<A>,
+    b: Option<B>,
 }
 
-/// The result of running a migratable step.
-#[derive(Debug, PartialEq)]
-pub enum StepResult {
-    InProgress { cursor: Cursor, steps_done: u32 },
-    Completed { steps_done: u32 },
+impl<A: MigrationStep, B: MigrationStep> Default for Parallel<A, B> {
+    fn default() -> Self {
+        Self {
+            a: A::should_run().then(A::default),
+            b: B::should_run().then(B::default),
+        }
+    }
 }
 
-#[impl_trait_for_tuples::impl_for_tuples(10)]
-#[tuple_types_custom_trait_bound(MigrationStep)]
-impl MigrateSequence for Tuple {
-    const VERSION_RANGE: (u16, u16) = {
-        let mut versions: (u16, u16) = (0, 0);
-        for_tuples!(
-            #(
-                match versions {
-                    (0, 0) => {
-                        versions = (Tuple::VERSION, Tuple::VERSION);
-                    },
-                    (min_version, last_version) if Tuple::VERSION == last_version + 1 => {
-                        versions = (min_version, Tuple::VERSION);
-                    },
-                    _ => panic!("Migrations must be ordered by their versions with no gaps.")
-                }
+impl<A: Encode, B: Encode> Encode for Parallel<A, B> {
+    fn encode_to<O: parity_scale_codec::Output + ?Sized>(&self, dest: &mut O) {
+        Self::encode_parts(self.a.as_ref(), self.b.as_ref(), dest);
+    }
+}
+
+impl<A: MigrationStep, B: MigrationStep> Decode for Parallel<A, B> {
+    fn decode<I: parity_scale_codec::Input>(
+        input: &mut I,
+    ) -> Result<Self, parity_scale_codec::Error> {
+        Self::decode_parts(A::CURSOR_SCHEMA, B::CURSOR_SCHEMA, input)
+    }
+}
+
+impl<A: Encode, B: Encode> Parallel<A, B> {
+    /// Encodes the parts `a` and `b`, each prefixed with its length.
+    fn encode_parts<O: parity_scale_codec::Output + ?Sized>(
+        a: Option<&A>,
+        b: Option<&B>,
+        dest: &mut O,
+    ) {
+        a.map(Encode::encode).encode_to(dest);
+        b.map(Encode::encode).encode_to(dest);
+    }
+}
+
+impl<A: MigrationStep, B: MigrationStep> Parallel<A, B> {
+    /// Decodes the parts encoded by [`Parallel::encode_parts`], persisted with the given schemas.
+    fn decode_parts<I: parity_scale_codec::Input>(
+        schema_a: u8,
+        schema_b: u8,
+        input: &mut I,
+    ) -> Result<Self, parity_scale_codec::Error> {
+        Ok(Self {
+            a: decode_part(schema_a, input)?,
+            b: decode_part(schema_b, input)?,
+        })
+    }
+
+    /// Passes the step made of the parts `a` and `b` to `checkpoint`.
+    fn checkpoint_parts(a: Option<&A>, b: Option<&B>, checkpoint: &mut dyn FnMut(&Self)) {
+        let mut encoded = Vec::new();
+        Self::encode_parts(a, b, &mut encoded);
+        match Self::decode(&mut &encoded[..]) {
+            Ok(step) => checkpoint(&step),
+            Err(error) => {
+                frame_support::defensive!("Failed to checkpoint the parallel step", error)
+            }
+        }
+    }
+
+    /// Whether the step is finished, given whether either part made progress.
+    fn finished(&self, progressed: bool) -> IsFinished {
+        if self.a.is_none() && self.b.is_none() {
+            IsFinished::Yes
+        } else if progressed {
+            IsFinished::No
+        } else {
+            IsFinished::Deferred
+        }
+    }
+}
+
+/// Decodes a part of a [`Parallel`] step persisted with `schema`.
+fn decode_part<M: MigrationStep, I: parity_scale_codec::Input>(
+    schema: u8,
+    input: &mut I,
+) -> Result<Option<M>, parity_scale_codec::Error> {
+    let Some(bytes) = Option::<Vec<u8>>::decode(input)? else {
+        return Ok(None);
+    };
+    let part = if schema == M::CURSOR_SCHEMA {
+        <M as Decode>::decode(&mut &bytes[..])
+    } else {
+        M::migrate_cursor(schema, &bytes)
+    };
+    part.map(Some)
+}
+
+/// Clears `part` once it is finished, returning whether it made progress.
+fn settle_part<M>(part: &mut Option<M>, finished: IsFinished) -> bool {
+    match finished {
+        IsFinished::Yes => {
+            *part = None;
+            true
+        }
+        IsFinished::No => true,
+        IsFinished::Deferred => false,
+    }
+}
+
+impl<A: MigrationStep, B: MigrationStep> MigrationStep for Parallel<A, B> {
+    const VERSION: u16 = {
+        assert!(A::VERSION == B::VERSION, "Parallel steps must have the same version.");
+        A::VERSION
+    };
+    // an `Option` byte and a compact length of up to 5 bytes per part
+    const MAX_CURSOR_LEN: u32 = A::MAX_CURSOR_LEN
+        .saturating_add(B::MAX_CURSOR_LEN)
+        .saturating_add(12);
+    const CURSOR_SCHEMA: u8 = {
+        assert!(
+            A::CURSOR_SCHEMA < 16 && B::CURSOR_SCHEMA < 16,
+            "Parallel steps must have cursor schemas lower than 16."
+        );
+        (A::CURSOR_SCHEMA << 4) | B::CURSOR_SCHEMA
+    };
+    const TRANSACTIONAL: bool = A::TRANSACTIONAL || B::TRANSACTIONAL;
+
+    fn max_step_weight() -> Weight {
+        step_weight::<A>().saturating_add(step_weight::<B>())
+    }
+
+    fn step(&mut self) -> (IsFinished, Weight) {
+        self.step_with_checkpoint(&mut |_| {})
+    }
+
+    fn step_with_checkpoint(&mut self, checkpoint: &mut dyn FnMut(&Self)) -> (IsFinished, Weight) {
+        let mut weight = Weight::zero();
+        let mut progressed = false;
+        if let Some(a) = &mut self.a {
+            let b = &self.b;
+            let (finished, step_weight) = a.step_with_checkpoint(&mut |a| {
+                Self::checkpoint_parts(Some(a), b.as_ref(), checkpoint)
+            });
+            add_weight(&mut weight, step_weight);
+            progressed |= settle_part(&mut self.a, finished);
+        }
+        if let Some(b) = &mut self.b {
+            let a = &self.a;
+            let (finished, step_weight) = b.step_with_checkpoint(&mut |b| {
+                Self::checkpoint_parts(a.as_ref(), Some(b), checkpoint)
+            });
+            add_weight(&mut weight, step_weight);
+            progressed |= settle_part(&mut self.b, finished);
+        }
+        (self.finished(progressed), weight)
+    }
+
+    fn try_step(&mut self) -> Result<(IsFinished, Weight), Weight> {
+        // a failed part fails the whole step, so that the writes of both are rolled back
+        let mut weight = Weight::zero();
+        let mut progressed = false;
+        if let Some(a) = &mut self.a {
+            let (finished, step_weight) = a.try_step().map_err(|step_weight| {
+                add_weight(&mut weight, step_weight);
+                weight
+            })?;
+            add_weight(&mut weight, step_weight);
+            progressed |= settle_part(&mut self.a, finished);
+        }
+        if let Some(b) = &mut self.b {
+            let (finished, step_weight) = b.try_step().map_err(|step_weight| {
+                add_weight(&mut weight, step_weight);
+                weight
+            })?;
+            add_weight(&mut weight, step_weight);
+            progressed |= settle_part(&mut self.b, finished);
+        }
+        Ok((self.finished(progressed), weight))
+    }
+
+    fn migrate_cursor(old_schema: u8, mut bytes: &[u8]) -> Result<Self, parity_scale_codec::Error> {
+        Self::decode_parts(old_schema >> 4, old_schema & 0x0f, &mut bytes)
+    }
+
+    fn name() -> &'static str {
+        core::any::type_name::<Self>()
+    }
+
+    fn post_step(&self, steps_done: u32) -> Weight {
+        let a = self
+            .a
+            .as_ref()
+            .map_or(Weight::zero(), |a| a.post_step(steps_done));
+        let b = self
+            .b
+            .as_ref()
+            .map_or(Weight::zero(), |b| b.post_step(steps_done));
+        a.saturating_add(b)
+    }
+
+    fn on_version_start(&mut self) -> Weight {
+        let a = self
+            .a
+            .as_mut()
+            .map_or(Weight::zero(), |a| a.on_version_start());
+        let b = self
+            .b
+            .as_mut()
+            .map_or(Weight::zero(), |b| b.on_version_start());
+        a.saturating_add(b)
+    }
+
+    fn check_dependencies() -> Result<(), &'static str> {
+        A::check_dependencies()?;
+        B::check_dependencies()
+    }
+
+    fn verify() -> Result<(), &'static str> {
+        A::verify()?;
+        B::verify()
+    }
+
+    fn max_steps_per_block() -> Option<u32> {
+        match (A::max_steps_per_block(), B::max_steps_per_block()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn encoded_len_bound() -> Option<usize> {
+        // the two `Option` bytes
+        Some(
+            A::encoded_len_bound()?
+                .saturating_add(B::encoded_len_bound()?)
+                .saturating_add(2),
+        )
+    }
+
+    fn describe_position(&self) -> alloc::string::String {
+        let describe = |position: Option<alloc::string::String>| {
+            position.unwrap_or_else(|| alloc::string::String::from("finished"))
+        };
+        alloc::format!(
+            "{}; {}",
+            describe(self.a.as_ref().map(A::describe_position)),
+            describe(self.b.as_ref().map(B::describe_position)),
+        )
+    }
+
+    fn estimate_remaining_items(&self) -> Option<u64> {
+        let a = match &self.a {
+            Some(a) => a.estimate_remaining_items()?,
+            None => 0,
+        };
+        let b = match &self.b {
+            Some(b) => b.estimate_remaining_items()?,
+            None => 0,
+        };
+        Some(a.saturating_add(b))
+    }
+
+    fn should_run() -> bool {
+        A::should_run() || B::should_run()
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade_step_with_weight() -> Result<(Vec<u8>, Weight), TryRuntimeError> {
+        let (a, a_weight) = A::pre_upgrade_step_with_weight()?;
+        let (b, b_weight) = B::pre_upgrade_step_with_weight()?;
+        Ok(((a, b).encode(), a_weight.saturating_add(b_weight)))
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade_step_with_weight(state: Vec<u8>) -> Result<Weight, TryRuntimeError> {
+        let (a, b) = <(Vec<u8>, Vec<u8>)>::decode(&mut &state[..]).map_err(|_| {
+            TryRuntimeError::Other("Failed to decode the parallel pre-upgrade state")
+        })?;
+        Ok(A::post_upgrade_step_with_weight(a)?
+            .saturating_add(B::post_upgrade_step_with_weight(b)?))
+    }
+}
+
+mod private {
+    use crate::{Guarded, MigrationStep};
+
+    pub trait Sealed {}
+
+    #[impl_trait_for_tuples::impl_for_tuples(10)]
+    #[tuple_types_custom_trait_bound(MigrationStep)]
+    impl Sealed for Tuple {}
+
+    impl<G, S: Sealed> Sealed for Guarded<G, S> {}
+}
+
+/// Defines a sequence of migrations.
+///
+/// The sequence must be defined by a tuple of migrations, each of which must implement the
+/// `MigrationStep` trait, optionally wrapped in a [`Guarded`]. Migrations must be ordered by their
+/// versions with no gaps.
+pub trait MigrateSequence: private::Sealed {
+    /// Returns the range of versions that this migrations sequence can handle.
+    /// Migrations must be ordered by their versions with no gaps.
+    const VERSION_RANGE: (u16, u16);
+
+    /// Returns the cursor the migration of the given version starts from, i.e. its default step
+    /// after running [`MigrationStep::on_version_start`].
+    ///
+    /// Panics if the version is not in the sequence, or its default step doesn't fit into a
+    /// [`Cursor`]. Runtime paths should use [`MigrateSequence::try_new`] instead.
+    fn new(version: StorageVersion) -> Cursor {
+        Self::try_new(version).unwrap_or_else(|error| match error {
+            MigrateError::UnsupportedVersion(_) => invalid_version(version),
+            _ => panic!("{PROOF_ENCODE}"),
+        })
+    }
+
+    /// Same as [`MigrateSequence::new`], but returns an error instead of panicking.
+    fn try_new(version: StorageVersion) -> Result<Cursor, MigrateError> {
+        Self::start(version).map(|(cursor, _)| cursor)
+    }
+
+    /// Same as [`MigrateSequence::try_new`], also returning the weight of the
+    /// [`MigrationStep::on_version_start`] hook it runs.
+    fn start(version: StorageVersion) -> Result<(Cursor, Weight), MigrateError>;
+
+    /// Execute the pre-checks of the given version, returning their state and weight.
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade_step(_version: StorageVersion) -> Result<(Vec<u8>, Weight), TryRuntimeError> {
+        Ok((Vec::new(), Weight::zero()))
+    }
+
+    /// Execute the post-checks of the given version, returning their weight.
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade_step(
+        _version: StorageVersion,
+        _state: Vec<u8>,
+    ) -> Result<Weight, TryRuntimeError> {
+        Ok(Weight::zero())
+    }
+
+    /// Returns the maximum weight of a single step of the given version.
+    fn max_step_weight(version: StorageVersion) -> Weight;
+
+    /// Returns the maximum number of steps of the given version run in a single block.
+    fn max_steps_per_block(version: StorageVersion) -> Option<u32>;
+
+    /// Decodes `cursor` for the given version and returns the
+    /// [`MigrationStep::describe_position`] of the step. Fails for the versions not in the
+    /// sequence.
+    fn describe_position(
+        version: StorageVersion,
+        cursor: &[u8],
+    ) -> Result<alloc::string::String, MigrateError>;
+
+    /// Decodes `cursor` for the given version and returns the
+    /// [`MigrationStep::estimate_remaining_items`] of the step, or `None` if the cursor can't be
+    /// decoded or the version is not in the sequence.
+    fn estimate_remaining_items(version: StorageVersion, cursor: &[u8]) -> Option<u64>;
+
+    /// Whether the given version is skipped when running all the migrations, see
+    /// [`MigrationStep::SKIP_IN_TEST_ALL`].
+    #[cfg(feature = "try-runtime")]
+    fn skip_in_test_all(version: StorageVersion) -> bool;
+
+    /// Checks the dependencies of the given version, see [`MigrationStep::check_dependencies`].
+    fn check_dependencies(version: StorageVersion) -> Result<(), &'static str>;
+
+    /// Whether the given version is a no-op, see [`MigrationStep::IS_NOOP`].
+    fn is_noop(version: StorageVersion) -> bool;
+
+    /// Checks the invariants of the given version, see [`MigrationStep::verify`]. Fails for the
+    /// versions not in the sequence.
+    fn verify(version: StorageVersion) -> Result<(), &'static str>;
+
+    /// Whether `cursor` decodes as the step of the given version, so that it can be resumed from.
+    /// Fails for the versions not in the sequence.
+    fn validate_cursor(version: StorageVersion, cursor: &[u8]) -> bool;
+
+    /// Re-encodes a `legacy` cursor of the given version, i.e. the bare encoded step persisted by
+    /// the releases predating the cursor header, into the current format. See
+    /// [`MigrateLegacyCursor`].
+    fn upgrade_legacy_cursor(
+        version: StorageVersion,
+        legacy: &[u8],
+    ) -> Result<Cursor, MigrateError>;
+
+    /// Returns the last version of the consecutive no-op migrations following `version`, up to
+    /// `target`, or `version` itself if the next one is not a no-op.
+    fn skip_noops(version: StorageVersion, target: StorageVersion) -> StorageVersion {
+        let mut version = version;
+        while let Some(next) = next_version(version) {
+            if next > target || !Self::is_noop(next) {
+                break;
+            }
+            version = next;
+        }
+        version
+    }
+
+    /// Execute the migratable step until the weight limit is reached.
+    ///
+    /// `checkpoint` is called with the intermediate cursors checkpointed by the steps, see
+    /// [`MigrationStep::step_with_checkpoint`].
+    fn steps(
+        version: StorageVersion,
+        cursor: &[u8],
+        weight_left: &mut Weight,
+        checkpoint: &mut dyn FnMut(Cursor),
+    ) -> StepResult;
+
+    /// Same as [`MigrateSequence::steps`], but returns an error instead of panicking if `cursor`
+    /// can't be decoded for `version`, `version` is not in the sequence, or the step doesn't fit
+    /// into a [`Cursor`] once it has to be persisted.
+    ///
+    /// `steps_in_block` steps of `version` already ran in the current block, e.g. in previous
+    /// `migrate` calls, and count towards its [`MigrationStep::max_steps_per_block`].
+    fn steps_checked(
+        version: StorageVersion,
+        cursor: &[u8],
+        weight_left: &mut Weight,
+        steps_in_block: u32,
+        checkpoint: &mut dyn FnMut(Cursor),
+    ) -> Result<StepResult, MigrateError>;
+
+    /// Verify that each migratable step fits into `max_cursor_len` bytes, and that its
+    /// `max_step_weight` is not greater than `max_block_weight`, returning the failed checks of
+    /// every step instead of stopping at the first one.
+    fn integrity_check(max_block_weight: Weight, max_cursor_len: usize) -> Vec<IntegrityError>;
+
+    /// Same as [`MigrateSequence::integrity_check`], panicking with every failed check.
+    fn integrity_test(max_block_weight: Weight, max_cursor_len: usize) {
+        assert_integrity(Self::integrity_check(max_block_weight, max_cursor_len));
+    }
+
+    /// Returns the metadata of every migratable of the sequence, ordered by version.
+    fn describe() -> Vec<MigrationDescriptor>;
+
+    /// Decodes `cursor` for the given version and returns the
+    /// [`MigrationStep::debug_description`] of the step. Fails for the versions not in the
+    /// sequence.
+    #[cfg(any(feature = "std", feature = "try-runtime"))]
+    fn describe_cursor(
+        version: StorageVersion,
+        cursor: &[u8],
+    ) -> Result<Option<alloc::string::String>, MigrateError>;
+
+    /// Returns whether migrating from `in_storage` to `target` is supported.
+    ///
+    /// A migratable is supported if `VERSION_RANGE` contains `in_storage + 1` and reaches at
+    /// least `target`, which must not be below `in_storage + 1`, so that the sequence can be capped
+    /// below its last version, and a single sequence can migrate chains at any of its versions.
+    fn is_upgrade_supported(in_storage: StorageVersion, target: StorageVersion) -> bool {
+        next_version(in_storage).is_some_and(|next| {
+            Self::first_version() <= next && next <= target && target <= Self::last_version()
+        })
+    }
+
+    /// Returns the first version of the sequence, i.e. `VERSION_RANGE.0`.
+    fn first_version() -> StorageVersion {
+        StorageVersion::new(Self::VERSION_RANGE.0)
+    }
+
+    /// Returns the last version of the sequence, i.e. `VERSION_RANGE.1`.
+    fn last_version() -> StorageVersion {
+        StorageVersion::new(Self::VERSION_RANGE.1)
+    }
+}
+
+/// The metadata of a migratable step, as returned by [`MigrateSequence::describe`].
+#[derive(Debug, PartialEq, Eq, Clone, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MigrationDescriptor {
+    /// The version the step migrates to.
+    pub version: u16,
+    /// The name of the step, see [`MigrationStep::name`].
+    pub name: Vec<u8>,
+    /// The maximum weight of a single step.
+    pub max_step_weight: Weight,
+}
+
+/// The result of running the migratable.
+#[derive(RuntimeDebug, PartialEq, Eq, Clone, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MigrateResult {
+    /// No migratable was performed
+    NoMigrationPerformed,
+    /// No migratable currently in progress
+    NoMigrationInProgress,
+    /// A migratable is in progress
+    InProgress { steps_done: u32 },
+    /// A migratable is in progress, and reached its
+    /// [`MigrationStep::max_steps_per_block`] for this block
+    Yielded { steps_done: u32 },
+    /// All migrations are completed
+    Completed,
+    /// The migratable in progress can't proceed, e.g. because its dependencies are not satisfied
+    Failed {
+        // like `DispatchError::Other`, the reason is not decoded
+        #[codec(skip)]
+        #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+        reason: &'static str,
+    },
+}
+
+/// Errors that can happen while migrating.
+///
+/// They convert into a [`DispatchError`], e.g. to be returned by a call with `?`.
+#[derive(RuntimeDebug, PartialEq, Eq, Clone, Encode, Decode, TypeInfo, MaxEncodedLen)]
+pub enum MigrateError {
+    /// The given version is not in the migration sequence.
+    UnsupportedVersion(u16),
+    /// The step doesn't fit into a [`Cursor`].
+    CursorOverflow,
+    /// The cursor can't be decoded for the version being migrated.
+    CursorDecode,
+    /// The cursor belongs to the given version instead of the one being migrated.
+    VersionMismatch(u16),
+    /// A migration is in progress.
+    MigrationInProgress,
+    /// The migration is halted after `Config::MAX_STEP_FAILURES` failed steps in a row, until its
+    /// cursor is replaced, e.g. through the generated `force_set_cursor` call.
+    Stalled,
+    /// A step failed, and its changes were rolled back.
+    StepFailed,
+    /// The given version is not migrated yet, so it can't be verified.
+    NotMigrated(u16),
+    /// The [`MigrationStep::verify`] checks of a migrated version failed.
+    VerificationFailed,
+    /// The next version couldn't be started once the previous one completed, e.g. because its
+    /// step doesn't fit into a [`Cursor`].
+    StartFailed,
+}
+
+impl MigrateError {
+    /// Returns a short description of the error, as used by its [`DispatchError`].
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UnsupportedVersion(_) => "Version not in the migration sequence",
+            Self::CursorOverflow => "Migration step doesn't fit into the cursor",
+            Self::CursorDecode => "Cursor doesn't decode for the version in progress",
+            Self::VersionMismatch(_) => "Cursor belongs to another version",
+            Self::MigrationInProgress => "There is a migration in progress",
+            Self::Stalled => "Migration halted after too many failed steps",
+            Self::StepFailed => "Migration step failed",
+            Self::NotMigrated(_) => "Version not migrated yet",
+            Self::VerificationFailed => "Migration verification failed",
+            Self::StartFailed => "Failed to start the next migration",
+        }
+    }
+}
+
+impl core::fmt::Display for MigrateError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version)
+            | Self::VersionMismatch(version)
+            | Self::NotMigrated(version) => {
+                write!(f, "{} ({version})", self.as_str())
+            }
+            _ => f.write_str(self.as_str()),
+        }
+    }
+}
+
+impl From<MigrateError> for DispatchError {
+    fn from(error: MigrateError) -> Self {
+        DispatchError::Other(error.as_str())
+    }
+}
+
+/// A failed integrity check of a migratable step.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum IntegrityError {
+    /// The ref time of the `max_step_weight` is greater than the one of the block.
+    RefTimeTooHigh {
+        version: u16,
+        ref_time: u64,
+        max: u64,
+    },
+    /// The proof size of the `max_step_weight` is greater than the one of the block.
+    ProofSizeTooHigh {
+        version: u16,
+        proof_size: u64,
+        max: u64,
+    },
+    /// The step defines neither `MAX_STEP_WEIGHT` nor `max_step_weight`.
+    MissingMaxStepWeight { version: u16 },
+    /// The step, along with the cursor header, doesn't fit into the cursor.
+    CursorTooLong {
+        version: u16,
+        len: usize,
+        max: usize,
+    },
+    /// The current storage version of the pallet is not the last version of its migrations, while
+    /// no `TARGET_VERSION` caps them.
+    StorageVersionMismatch { current: u16, last: u16 },
+    /// The `MAX_CURSOR_LEN` of the step is lower than its `encoded_len_bound`.
+    CursorLenTooLow {
+        version: u16,
+        declared: u32,
+        bound: usize,
+    },
+}
+
+impl core::fmt::Display for IntegrityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::RefTimeTooHigh { version, ref_time, max } => write!(
+                f,
+                "Invalid max_step_weight for Migration {version}. Ref time {ref_time} should be lower than {max}",
+            ),
+            Self::ProofSizeTooHigh { version, proof_size, max } => write!(
+                f,
+                "Invalid max_step_weight for Migration {version}. Proof size {proof_size} should be lower than {max}",
+            ),
+            Self::MissingMaxStepWeight { version } => write!(
+                f,
+                "Migration {version} must define either MAX_STEP_WEIGHT or max_step_weight",
+            ),
+            Self::CursorTooLong { version, len, max } => write!(
+                f,
+                "Migration {version} has size {len} which is bigger than the maximum of {max}",
+            ),
+            Self::StorageVersionMismatch { current, last } => write!(
+                f,
+                "The current storage version {current} should match the last version {last} of `Config::Migrations`",
+            ),
+            Self::CursorLenTooLow { version, declared, bound } => write!(
+                f,
+                "Migration {version} declares MAX_CURSOR_LEN {declared}, lower than its encoded length of up to {bound}",
+            ),
+        }
+    }
+}
+
+/// Panics listing every error, if any.
+#[doc(hidden)]
+pub fn assert_integrity(errors: Vec<IntegrityError>) {
+    if errors.is_empty() {
+        return;
+    }
+    let mut message = alloc::string::String::new();
+    for error in errors {
+        use core::fmt::Write;
+        let _ = write!(message, "\n- {error}");
+    }
+    panic!("Integrity checks failed:{message}");
+}
+
+/// Panics listing every error of every pallet, if any. Used by [`integrity_test_all!`].
+#[doc(hidden)]
+pub fn assert_pallets_integrity(
+    pallets: impl IntoIterator<Item = (&'static str, Vec<IntegrityError>)>,
+) {
+    let mut message = alloc::string::String::new();
+    for (pallet, errors) in pallets {
+        for error in errors {
+            use core::fmt::Write;
+            let _ = write!(message, "\n- {pallet}: {error}");
+        }
+    }
+    if !message.is_empty() {
+        panic!("Integrity checks failed:{message}");
+    }
+}
+
+/// Checks the migrations of every given pallet against the `max_block` weight of the runtime,
+/// panicking with the failed checks of all of them, along with the name of their pallet.
+///
+/// ```ignore
+/// #[test]
+/// fn migrations_integrity() {
+///     migratable::integrity_test_all!(
+///         pallet_a::Migration<Runtime>,
+///         pallet_b::Migration<Runtime>,
+///     );
+/// }
+/// ```
+#[macro_export]
+macro_rules! integrity_test_all {
+    ($($migration:ty),+ $(,)?) => {
+        $crate::assert_pallets_integrity([
+            $((
+                <$migration as $crate::PalletMigration>::pallet_name(),
+                <$migration as $crate::PalletMigration>::integrity_check(),
+            ),)+
+        ])
+    };
+}
+
+/// Expands to the given items only with the `test-utils` feature of this crate, as the `pallet`
+/// macro can't tell whether it is enabled.
+#[cfg(feature = "test-utils")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __test_utils {
+    ($($item:item)*) => {
+        $($item)*
+    };
+}
+
+/// Expands to the given items only with the `test-utils` feature of this crate, as the `pallet`
+/// macro can't tell whether it is enabled.
+#[cfg(not(feature = "test-utils"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __test_utils {
+    ($($item:item)*) => {};
+}
+
+/// The result of running a migratable step.
+#[derive(RuntimeDebug, PartialEq, Eq, Clone, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StepResult {
+    InProgress {
+        cursor: Cursor,
+        steps_done: u32,
+    },
+    /// The step reached its [`MigrationStep::max_steps_per_block`].
+    Yielded {
+        cursor: Cursor,
+        steps_done: u32,
+    },
+    Completed {
+        steps_done: u32,
+    },
+    /// A transactional step failed, see [`MigrationStep::try_step`]. `cursor` resumes from the
+    /// failed step.
+    Failed {
+        cursor: Cursor,
+        steps_done: u32,
+    },
+}
+
+impl StepResult {
+    /// The number of steps run by the call, which fits into a `u32` as it is bounded by the
+    /// weight of a block. Totals across blocks are accumulated with [`accrue_steps`].
+    pub fn steps_done(&self) -> u32 {
+        match self {
+            Self::InProgress { steps_done, .. }
+            | Self::Yielded { steps_done, .. }
+            | Self::Completed { steps_done }
+            | Self::Failed { steps_done, .. } => *steps_done,
+        }
+    }
+}
+
+/// Adds the `steps_done` of a single call to the cumulative `total` of a version, as stored in
+/// `MigrationStepsDone`.
+pub fn accrue_steps(total: u64, steps_done: u32) -> u64 {
+    total.saturating_add(steps_done.into())
+}
+
+/// The progress of a migration after a `migrate` call, written to the off-chain indexing storage
+/// when `Config::OFFCHAIN_INDEX_PROGRESS` is set, under [`offchain_progress_key`].
+#[derive(RuntimeDebug, PartialEq, Eq, Clone, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MigrationProgressRecord {
+    /// The version being migrated.
+    pub version: u16,
+    /// The steps run by the call.
+    pub steps_done: u32,
+    /// The weight consumed by the migrations so far, including the call.
+    pub weight_consumed: Weight,
+}
+
+/// The prefix of the off-chain indexing keys of the [`MigrationProgressRecord`]s.
+pub const OFFCHAIN_PROGRESS_PREFIX: &[u8] = b"migratable::progress";
+
+/// Returns the off-chain indexing key of the [`MigrationProgressRecord`] of `pallet` at `block`.
+pub fn offchain_progress_key<BlockNumber: Encode>(pallet: &str, block: BlockNumber) -> Vec<u8> {
+    (OFFCHAIN_PROGRESS_PREFIX, pallet.as_bytes(), block).encode()
+}
+
+/// Writes `record` to the off-chain indexing storage, under [`offchain_progress_key`].
+///
+/// A later record of the same pallet in the same block overwrites it.
+pub fn index_progress<BlockNumber: Encode>(
+    pallet: &str,
+    block: BlockNumber,
+    record: &MigrationProgressRecord,
+) {
+    frame_support::sp_io::offchain_index::set(
+        &offchain_progress_key(pallet, block),
+        &record.encode(),
+    );
+}
+
+/// A preview of the next step of the migration in progress.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StepPreview {
+    /// The version being migrated.
+    pub version: u16,
+    /// The [`MigrationStep::describe_position`] of the step, or the error decoding its cursor.
+    pub position: Result<alloc::string::String, MigrateError>,
+}
+
+/// The state of the migrations of a pallet, stored by the `pallet` macro when given the `state`
+/// argument.
+#[derive(RuntimeDebug, Default, PartialEq, Eq, Clone, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MigrationState {
+    /// No migration has been started yet.
+    #[default]
+    Pending,
+    /// A migration is in progress, resuming from the given cursor.
+    InProgress(Cursor),
+    /// The last migration started has been completed.
+    Completed,
+}
+
+/// Moves the `MigrationInProgress` cursor of pallet `P` into its `MigrationState` storage.
+///
+/// Meant to be included in the runtime upgrade that turns on the `state` argument of the
+/// `pallet` macro, so that a migration in progress is not lost.
+pub struct MigrateToStateStorage<P, DbWeight>(core::marker::PhantomData<(P, DbWeight)>);
+
+impl<P, DbWeight> frame_support::traits::OnRuntimeUpgrade for MigrateToStateStorage<P, DbWeight>
+where
+    P: frame_support::traits::PalletInfoAccess,
+    DbWeight: frame_support::traits::Get<frame_support::weights::RuntimeDbWeight>,
+{
+    fn on_runtime_upgrade() -> Weight {
+        let pallet = P::name().as_bytes();
+        let cursor = frame_support::storage::migration::take_storage_value::<Cursor>(
+            pallet,
+            b"MigrationInProgress",
+            &[],
+        );
+        if let Some(cursor) = cursor {
+            frame_support::storage::migration::put_storage_value(
+                pallet,
+                b"MigrationState",
+                &[],
+                MigrationState::InProgress(cursor),
+            );
+            DbWeight::get().reads_writes(1, 2)
+        } else {
+            DbWeight::get().reads(1)
+        }
+    }
+}
+
+/// Re-encodes the `MigrationInProgress` cursor of pallet `P`, whose migrations are `S`, from the
+/// bare encoded step persisted by the releases predating the cursor header into the current
+/// format, see [`MigrationStep::CURSOR_SCHEMA`].
+///
+/// Meant to be included, before the migrations of `P`, in the runtime upgrade that brings in the
+/// cursor header while a migration is in progress. It must run before [`MigrateToStateStorage`] if
+/// both are included. A cursor that doesn't decode as the step of the version in progress is left
+/// untouched, halting the migration until it is replaced.
+pub struct MigrateLegacyCursor<P, S, DbWeight>(core::marker::PhantomData<(P, S, DbWeight)>);
+
+impl<P, S, DbWeight> frame_support::traits::OnRuntimeUpgrade for MigrateLegacyCursor<P, S, DbWeight>
+where
+    P: frame_support::traits::PalletInfoAccess,
+    S: MigrateSequence,
+    DbWeight: frame_support::traits::Get<frame_support::weights::RuntimeDbWeight>,
+{
+    fn on_runtime_upgrade() -> Weight {
+        let pallet = P::name().as_bytes();
+        let Some(legacy) = frame_support::storage::migration::get_storage_value::<Cursor>(
+            pallet,
+            b"MigrationInProgress",
+            &[],
+        ) else {
+            return DbWeight::get().reads(1);
+        };
+        // a migration in progress is always upgrading to the next version
+        let upgraded = next_version(StorageVersion::get::<P>())
+            .ok_or(MigrateError::UnsupportedVersion(u16::MAX))
+            .and_then(|version| S::upgrade_legacy_cursor(version, &legacy));
+        match upgraded {
+            Ok(cursor) => {
+                frame_support::storage::migration::put_storage_value(
+                    pallet,
+                    b"MigrationInProgress",
+                    &[],
+                    cursor,
+                );
+                DbWeight::get().reads_writes(2, 1)
+            }
+            Err(error) => {
+                frame_support::defensive!("Failed to upgrade the legacy cursor", error);
+                DbWeight::get().reads(2)
+            }
+        }
+    }
+}
+
+/// Re-encodes the `MigrationStepsDone` counter of pallet `P` from the `u32` stored by the
+/// releases predating its widening into a `u64`.
+///
+/// Meant to be included, before the migrations of `P`, in the runtime upgrade that brings in the
+/// `u64` counter while a migration is in progress. A counter already stored as a `u64` is left
+/// untouched.
+pub struct MigrateStepsDoneToU64<P, DbWeight>(core::marker::PhantomData<(P, DbWeight)>);
+
+impl<P, DbWeight> frame_support::traits::OnRuntimeUpgrade for MigrateStepsDoneToU64<P, DbWeight>
+where
+    P: frame_support::traits::PalletInfoAccess,
+    DbWeight: frame_support::traits::Get<frame_support::weights::RuntimeDbWeight>,
+{
+    fn on_runtime_upgrade() -> Weight {
+        let key =
+            frame_support::storage::storage_prefix(P::name().as_bytes(), b"MigrationStepsDone");
+        let Some(raw) = frame_support::storage::unhashed::get_raw(&key) else {
+            return DbWeight::get().reads(1);
+        };
+        // a `u64` counter is 8 bytes long, the legacy `u32` one only 4
+        let Ok(legacy) = <[u8; 4]>::try_from(&raw[..]) else {
+            return DbWeight::get().reads(1);
+        };
+        frame_support::storage::unhashed::put(&key, &u64::from(u32::from_le_bytes(legacy)));
+        DbWeight::get().reads_writes(1, 1)
+    }
+}
+
+/// The status of the migrations of a pallet, as exposed to off-chain clients.
+#[derive(RuntimeDebug, PartialEq, Eq, Clone, Encode, Decode, TypeInfo, MaxEncodedLen)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MigrationStatus {
+    /// The on-chain storage version is behind the pallet's, but no migration has started yet.
+    NotStarted,
+    /// A migration towards `version` is in progress.
+    InProgress { version: u16, cursor_len: u32 },
+    /// The on-chain storage version matches the pallet's.
+    Completed,
+}
+
+/// The migrations of a pallet.
+///
+/// Implemented by the `Migration` struct generated by the `pallet` macro, so that the migrations
+/// of several pallets can be handled together at the runtime level.
+pub trait PalletMigration {
+    /// Returns the name of the pallet in the runtime.
+    fn pallet_name() -> &'static str;
+
+    /// Returns the status of the migrations of the pallet.
+    fn status() -> MigrationStatus;
+
+    /// Returns whether the migrations of the pallet, if not started, can be, i.e. whether
+    /// `Migrations` supports the upgrade from the on-chain storage version to the target version.
+    fn can_start() -> bool;
+
+    /// Returns the weight of starting the migrations of the pallet on runtime upgrade, apart from
+    /// the [`MigrationStep::on_version_start`] hook of the first version.
+    fn start_weight() -> Weight;
+
+    /// Runs the migrations of the pallet with up to `remaining_weight`, as its `on_idle` hook does,
+    /// reducing it by the weight consumed, which is returned.
+    fn migrate_idle(remaining_weight: &mut Weight) -> Weight;
+
+    /// Returns the failed integrity checks of the migrations of the pallet, see
+    /// [`MigrateSequence::integrity_check`].
+    fn integrity_check() -> Vec<IntegrityError>;
+}
+
+/// Observes the progress of the migrations of a pallet, e.g. to push metrics.
+///
+/// Set through the `MigrationObserver` type of the pallet's `Config`, with `()` ignoring every
+/// notification. The weight of the callbacks is not accounted for, so they should be kept light.
+pub trait MigrationObserver {
+    /// Called after running the steps of `version`, with the weight they consumed.
+    fn on_step(_version: StorageVersion, _steps_done: u32, _weight: Weight) {}
+
+    /// Called once `version` is completed and stored on-chain.
+    fn on_version_completed(_version: StorageVersion) {}
+
+    /// Called once all the migrations are completed.
+    fn on_all_completed() {}
+}
+
+impl MigrationObserver for () {}
+
+/// Defines a migration sequence as a type alias, checking at the definition site that its
+/// migrations are ordered by their versions with no gaps.
+///
+/// The first and last versions of the sequence can also be given a name, as `u16` constants.
+///
+/// ```ignore
+/// migratable::define_sequence! {
+///     /// The migrations of `my_pallet`.
+///     pub type MyPalletMigrations = (v2::Migration<Runtime>, v3::Migration<Runtime>);
+///     first = MY_PALLET_FIRST_VERSION, last = MY_PALLET_LAST_VERSION;
+/// }
+/// ```
+#[macro_export]
+macro_rules! define_sequence {
+    (
+        $(#[$attr:meta])*
+        $vis:vis type $name:ident = ($($step:ty),+ $(,)?);
+        $(first = $first:ident, last = $last:ident;)?
+    ) => {
+        $(#[$attr])*
+        $vis type $name = ($($step,)+);
+
+        // evaluating the range fails the build if the versions have gaps
+        const _: () = {
+            let (first, last) = <$name as $crate::MigrateSequence>::VERSION_RANGE;
+            assert!(first <= last);
+        };
+
+        $(
+            $vis const $first: u16 = <$name as $crate::MigrateSequence>::VERSION_RANGE.0;
+            $vis const $last: u16 = <$name as $crate::MigrateSequence>::VERSION_RANGE.1;
+        )?
+    };
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(10)]
+#[tuple_types_custom_trait_bound(MigrationStep)]
+impl MigrateSequence for Tuple {
+    const VERSION_RANGE: (u16, u16) = {
+        let mut versions: (u16, u16) = (0, 0);
+        for_tuples!(
+            #(
+                match versions {
+                    (0, 0) => {
+                        versions = (Tuple::VERSION, Tuple::VERSION);
+                    },
+                    (min_version, last_version) => match last_version.checked_add(1) {
+                        Some(next_version) if next_version == Tuple::VERSION => {
+                            versions = (min_version, Tuple::VERSION);
+                        },
+                        Some(_) => panic!("Migrations must be ordered by their versions with no gaps."),
+                        None => panic!(
+                            "Migrations can't go past version 65535, the last `u16` storage version."
+                        ),
+                    },
+                }
             )*
         );
         versions
     };
 
-    fn new(version: StorageVersion) -> Cursor {
+    fn start(version: StorageVersion) -> Result<(Cursor, Weight), MigrateError> {
         for_tuples!(
             #(
                 if version == Tuple::VERSION {
-                    return Tuple::default().encode().try_into().expect(PROOF_ENCODE)
+                    if !Tuple::should_run() {
+                        return Ok((Cursor::default(), Weight::zero()))
+                    }
+                    let mut migration = Tuple::default();
+                    let weight = migration.on_version_start();
+                    return try_encode_cursor(&migration).map(|cursor| (cursor, weight))
                 }
             )*
         );
-        invalid_version(version)
+        Err(MigrateError::UnsupportedVersion(storage_version_number(version)))
     }
 
     #[cfg(feature = "try-runtime")]
     /// Execute the pre-checks of the step associated with this version.
-    fn pre_upgrade_step(version: StorageVersion) -> Result<Vec<u8>, TryRuntimeError> {
+    fn pre_upgrade_step(version: StorageVersion) -> Result<(Vec<u8>, Weight), TryRuntimeError> {
         for_tuples!(
             #(
                 if version == Tuple::VERSION {
-                    return Tuple::pre_upgrade_step()
+                    if !Tuple::should_run() {
+                        return Ok((Vec::new(), Weight::zero()))
+                    }
+                    return Tuple::pre_upgrade_step_with_weight()
                 }
             )*
         );
@@ -258,45 +1981,365 @@ impl MigrateSequence for Tuple {
 
     #[cfg(feature = "try-runtime")]
     /// Execute the post-checks of the step associated with this version.
-    fn post_upgrade_step(version: StorageVersion, state: Vec<u8>) -> Result<(), TryRuntimeError> {
+    fn post_upgrade_step(
+        version: StorageVersion,
+        state: Vec<u8>,
+    ) -> Result<Weight, TryRuntimeError> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    if !Tuple::should_run() {
+                        return Ok(Weight::zero())
+                    }
+                    return Tuple::post_upgrade_step_with_weight(state)
+                }
+            )*
+        );
+        invalid_version(version)
+    }
+
+    fn max_step_weight(version: StorageVersion) -> Weight {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    return step_weight::<Tuple>()
+                }
+            )*
+        );
+        invalid_version(version)
+    }
+
+    fn describe_position(
+        version: StorageVersion,
+        cursor: &[u8],
+    ) -> Result<alloc::string::String, MigrateError> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    if !Tuple::should_run() {
+                        return Ok(alloc::string::String::new())
+                    }
+                    return try_decode_cursor::<Tuple>(cursor)
+                        .map(|migration| migration.describe_position())
+                }
+            )*
+        );
+        Err(MigrateError::UnsupportedVersion(storage_version_number(version)))
+    }
+
+    fn estimate_remaining_items(version: StorageVersion, cursor: &[u8]) -> Option<u64> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    if !Tuple::should_run() {
+                        return Some(0)
+                    }
+                    return try_decode_cursor::<Tuple>(cursor)
+                        .ok()
+                        .and_then(|migration| migration.estimate_remaining_items())
+                }
+            )*
+        );
+        None
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn skip_in_test_all(version: StorageVersion) -> bool {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    return Tuple::SKIP_IN_TEST_ALL
+                }
+            )*
+        );
+        invalid_version(version)
+    }
+
+    fn check_dependencies(version: StorageVersion) -> Result<(), &'static str> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    if !Tuple::should_run() {
+                        return Ok(())
+                    }
+                    return Tuple::check_dependencies()
+                }
+            )*
+        );
+        invalid_version(version)
+    }
+
+    fn is_noop(version: StorageVersion) -> bool {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    return Tuple::IS_NOOP
+                }
+            )*
+        );
+        false
+    }
+
+    fn verify(version: StorageVersion) -> Result<(), &'static str> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    if !Tuple::should_run() {
+                        return Ok(())
+                    }
+                    return Tuple::verify()
+                }
+            )*
+        );
+        Err("Version not in the migration sequence")
+    }
+
+    fn validate_cursor(version: StorageVersion, cursor: &[u8]) -> bool {
         for_tuples!(
             #(
                 if version == Tuple::VERSION {
-                    return Tuple::post_upgrade_step(state)
+                    // the cursor is ignored
+                    if !Tuple::should_run() {
+                        return true
+                    }
+                    return try_decode_cursor::<Tuple>(cursor).is_ok()
+                }
+            )*
+        );
+        false
+    }
+
+    fn upgrade_legacy_cursor(
+        version: StorageVersion,
+        mut legacy: &[u8],
+    ) -> Result<Cursor, MigrateError> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    let migration = <Tuple as Decode>::decode(&mut legacy)
+                        .map_err(|_| MigrateError::CursorDecode)?;
+                    return try_encode_cursor(&migration)
+                }
+            )*
+        );
+        Err(MigrateError::UnsupportedVersion(storage_version_number(version)))
+    }
+
+    fn max_steps_per_block(version: StorageVersion) -> Option<u32> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    return Tuple::max_steps_per_block()
                 }
             )*
         );
         invalid_version(version)
     }
 
-    fn steps(version: StorageVersion, mut cursor: &[u8], weight_left: &mut Weight) -> StepResult {
+    fn steps(
+        version: StorageVersion,
+        cursor: &[u8],
+        weight_left: &mut Weight,
+        checkpoint: &mut dyn FnMut(Cursor),
+    ) -> StepResult {
         for_tuples!(
             #(
                 if version == Tuple::VERSION {
-                    let mut migration = <Tuple as Decode>::decode(&mut cursor)
-                        .expect(PROOF_DECODE);
-                    let max_weight = Tuple::max_step_weight();
-                    let mut steps_done = 0;
-                    while weight_left.all_gt(max_weight) {
-                        let (finished, weight) = migration.step();
-                        steps_done.saturating_accrue(1);
-                        weight_left.saturating_reduce(weight);
-                        if matches!(finished, IsFinished::Yes) {
-                            return StepResult::Completed{ steps_done }
-                        }
+                    if !Tuple::should_run() {
+                        return StepResult::Completed { steps_done: 0 }
                     }
-                    return StepResult::InProgress{cursor: migration.encode().try_into().expect(PROOF_ENCODE), steps_done }
+                    return run_steps(decode_cursor::<Tuple>(cursor), weight_left, 0, checkpoint)
+                        .expect(PROOF_ENCODE)
                 }
             )*
         );
         invalid_version(version)
     }
 
-    fn integrity_test(max_block_weight: Weight) {
+    fn steps_checked(
+        version: StorageVersion,
+        cursor: &[u8],
+        weight_left: &mut Weight,
+        steps_in_block: u32,
+        checkpoint: &mut dyn FnMut(Cursor),
+    ) -> Result<StepResult, MigrateError> {
+        for_tuples!(
+            #(
+                if version == Tuple::VERSION {
+                    if !Tuple::should_run() {
+                        return Ok(StepResult::Completed { steps_done: 0 })
+                    }
+                    return try_decode_cursor::<Tuple>(cursor)
+                        .and_then(|migration| {
+                            run_steps(migration, weight_left, steps_in_block, checkpoint)
+                        })
+                }
+            )*
+        );
+        Err(MigrateError::UnsupportedVersion(storage_version_number(version)))
+    }
+
+    fn integrity_check(max_block_weight: Weight, max_cursor_len: usize) -> Vec<IntegrityError> {
+        let mut errors = Vec::new();
+        for_tuples!(
+            #(
+                errors.extend(Tuple::integrity_check(max_block_weight, max_cursor_len));
+            )*
+        );
+        errors
+    }
+
+    fn describe() -> Vec<MigrationDescriptor> {
+        let mut descriptors = Vec::new();
+        for_tuples!(
+            #(
+                descriptors.push(MigrationDescriptor {
+                    version: Tuple::VERSION,
+                    name: Tuple::name().as_bytes().to_vec(),
+                    max_step_weight: step_weight::<Tuple>(),
+                });
+            )*
+        );
+        descriptors
+    }
+
+    #[cfg(any(feature = "std", feature = "try-runtime"))]
+    fn describe_cursor(
+        version: StorageVersion,
+        cursor: &[u8],
+    ) -> Result<Option<alloc::string::String>, MigrateError> {
         for_tuples!(
             #(
-                Tuple::integrity_test(max_block_weight);
+                if version == Tuple::VERSION {
+                    return try_decode_cursor::<Tuple>(cursor)
+                        .map(|migration| migration.debug_description())
+                }
             )*
         );
+        Err(MigrateError::UnsupportedVersion(storage_version_number(version)))
+    }
+}
+
+/// A check guarding a [`Guarded`] sequence.
+pub trait GuardStep {
+    /// Checks the precondition of the sequence, e.g. that a required value is set.
+    fn check() -> Result<(), &'static str>;
+}
+
+/// A [`MigrateSequence`] that runs the [`GuardStep`] `G` before the first version of `S`.
+///
+/// The guard is checked along with the dependencies of the first version of `S` only, i.e. before
+/// its first step, so a migration resuming past that version is not guarded. A failing guard fails
+/// the migration before any step of the version runs, and is checked again in the next block.
+/// Everything else is forwarded to `S`.
+pub struct Guarded<G, S>(core::marker::PhantomData<(G, S)>);
+
+impl<G: GuardStep, S: MigrateSequence> MigrateSequence for Guarded<G, S> {
+    const VERSION_RANGE: (u16, u16) = S::VERSION_RANGE;
+
+    fn start(version: StorageVersion) -> Result<(Cursor, Weight), MigrateError> {
+        S::start(version)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn pre_upgrade_step(version: StorageVersion) -> Result<(Vec<u8>, Weight), TryRuntimeError> {
+        S::pre_upgrade_step(version)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn post_upgrade_step(
+        version: StorageVersion,
+        state: Vec<u8>,
+    ) -> Result<Weight, TryRuntimeError> {
+        S::post_upgrade_step(version, state)
+    }
+
+    fn max_step_weight(version: StorageVersion) -> Weight {
+        S::max_step_weight(version)
+    }
+
+    fn max_steps_per_block(version: StorageVersion) -> Option<u32> {
+        S::max_steps_per_block(version)
+    }
+
+    fn describe_position(
+        version: StorageVersion,
+        cursor: &[u8],
+    ) -> Result<alloc::string::String, MigrateError> {
+        S::describe_position(version, cursor)
+    }
+
+    fn estimate_remaining_items(version: StorageVersion, cursor: &[u8]) -> Option<u64> {
+        S::estimate_remaining_items(version, cursor)
+    }
+
+    #[cfg(feature = "try-runtime")]
+    fn skip_in_test_all(version: StorageVersion) -> bool {
+        S::skip_in_test_all(version)
+    }
+
+    fn check_dependencies(version: StorageVersion) -> Result<(), &'static str> {
+        if storage_version_number(version) == S::VERSION_RANGE.0 {
+            G::check()?;
+        }
+        S::check_dependencies(version)
+    }
+
+    fn is_noop(version: StorageVersion) -> bool {
+        S::is_noop(version)
+    }
+
+    fn verify(version: StorageVersion) -> Result<(), &'static str> {
+        S::verify(version)
+    }
+
+    fn validate_cursor(version: StorageVersion, cursor: &[u8]) -> bool {
+        S::validate_cursor(version, cursor)
+    }
+
+    fn upgrade_legacy_cursor(
+        version: StorageVersion,
+        legacy: &[u8],
+    ) -> Result<Cursor, MigrateError> {
+        S::upgrade_legacy_cursor(version, legacy)
+    }
+
+    fn steps(
+        version: StorageVersion,
+        cursor: &[u8],
+        weight_left: &mut Weight,
+        checkpoint: &mut dyn FnMut(Cursor),
+    ) -> StepResult {
+        S::steps(version, cursor, weight_left, checkpoint)
+    }
+
+    fn steps_checked(
+        version: StorageVersion,
+        cursor: &[u8],
+        weight_left: &mut Weight,
+        steps_in_block: u32,
+        checkpoint: &mut dyn FnMut(Cursor),
+    ) -> Result<StepResult, MigrateError> {
+        S::steps_checked(version, cursor, weight_left, steps_in_block, checkpoint)
+    }
+
+    fn integrity_check(max_block_weight: Weight, max_cursor_len: usize) -> Vec<IntegrityError> {
+        S::integrity_check(max_block_weight, max_cursor_len)
+    }
+
+    fn describe() -> Vec<MigrationDescriptor> {
+        S::describe()
+    }
+
+    #[cfg(any(feature = "std", feature = "try-runtime"))]
+    fn describe_cursor(
+        version: StorageVersion,
+        cursor: &[u8],
+    ) -> Result<Option<alloc::string::String>, MigrateError> {
+        S::describe_cursor(version, cursor)
+    }
+
+    fn is_upgrade_supported(in_storage: StorageVersion, target: StorageVersion) -> bool {
+        S::is_upgrade_supported(in_storage, target)
     }
 }
@@ -0,0 +1,15 @@
+//! The items commonly needed to write and wire migrations, to be glob imported with
+//! `use migratable::prelude::*;`.
+//!
+//! Items mostly needed by tooling, such as [`MigrationStatus`](crate::MigrationStatus) or the
+//! [`simulation`](crate::simulation), are left out.
+
+pub use crate::{
+    config, hooks,
+    iter::WeightBoundedIter,
+    pallet,
+    weights::{SubstrateWeight, WeightInfo},
+    Cursor, FromOnRuntimeUpgrade, GuardStep, Guarded, IsFinished, MapMigration, MigrateResult,
+    MigrateSequence, MigrationObserver, MigrationPhases, MigrationStep, NoopMigration, OneShot,
+    OneShotMigration, PalletMigration, Parallel, PhasedMigration, StepResult, VersionBumpMigration,
+};
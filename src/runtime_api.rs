@@ -0,0 +1,53 @@
+//! Runtime API exposing the status of the migrations to off-chain tooling.
+//!
+//! Runtimes implement [`MigrationStatusApi`] in their `impl_runtime_apis!` block, listing the
+//! `Migration` structs of the pallets to expose:
+//!
+//! ```ignore
+//! impl migratable::runtime_api::MigrationStatusApi<Block> for Runtime {
+//!     fn migration_status(pallet: Vec<u8>) -> Option<migratable::MigrationStatus> {
+//!         migratable::runtime_api::migration_status::<(
+//!             pallet_a::Migration<Runtime>,
+//!             pallet_b::Migration<Runtime>,
+//!         )>(&pallet)
+//!     }
+//! }
+//! ```
+
+use crate::{MigrationStatus, PalletMigration};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+    /// API to query the status of the migrations of the pallets using `migratable`.
+    pub trait MigrationStatusApi {
+        /// Returns the status of the migrations of `pallet`, given its name in the runtime, or
+        /// `None` if the pallet is not exposed through this API.
+        fn migration_status(pallet: Vec<u8>) -> Option<MigrationStatus>;
+    }
+}
+
+/// A set of pallet migrations whose status can be queried by pallet name.
+pub trait MigrationStatusSet {
+    /// Returns the status of the migrations of `pallet`, if it belongs to the set.
+    fn migration_status(pallet: &[u8]) -> Option<MigrationStatus>;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+#[tuple_types_custom_trait_bound(PalletMigration)]
+impl MigrationStatusSet for Tuple {
+    fn migration_status(pallet: &[u8]) -> Option<MigrationStatus> {
+        for_tuples!(
+            #(
+                if Tuple::pallet_name().as_bytes() == pallet {
+                    return Some(Tuple::status())
+                }
+            )*
+        );
+        None
+    }
+}
+
+/// Implements [`MigrationStatusApi::migration_status`] over the given tuple of pallet migrations.
+pub fn migration_status<Pallets: MigrationStatusSet>(pallet: &[u8]) -> Option<MigrationStatus> {
+    Pallets::migration_status(pallet)
+}
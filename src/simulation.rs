@@ -0,0 +1,171 @@
+//! Offline simulation of how a migration sequence unfolds across blocks.
+//!
+//! The simulation replays the `on_idle` driver, running the real [`MigrateSequence::steps`] logic
+//! against estimated step counts, assuming every step consumes its full `max_step_weight`. It is meant for
+//! documentation and pre-deployment review of a rollout, not for on-chain use.
+
+use crate::{
+    encode_cursor, next_version, storage_version_number, Cursor, IsFinished, MigrateSequence,
+    MigrationStep, StepResult,
+};
+use core::cell::Cell;
+use frame_support::pallet_prelude::{StorageVersion, Weight};
+use parity_scale_codec::{Decode, Encode};
+
+/// The estimates the simulation is driven by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationInput {
+    /// Estimated number of steps needed to complete each version. Versions not listed here are
+    /// assumed to complete in a single step.
+    pub steps_per_version: Vec<(u16, u32)>,
+    /// Idle weight available for migrations in every block.
+    pub block_weight: Weight,
+    /// Maximum number of blocks to simulate.
+    pub max_blocks: u32,
+}
+
+impl SimulationInput {
+    fn steps_for(&self, version: u16) -> u32 {
+        self.steps_per_version
+            .iter()
+            .find(|(v, _)| *v == version)
+            .map_or(1, |(_, steps)| (*steps).max(1))
+    }
+}
+
+/// The migration work performed in a single simulated block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockPlan {
+    /// Index of the block, starting at zero for the first block after the upgrade.
+    pub block: u32,
+    /// The versions being migrated in this block, with the number of steps run for each of them.
+    pub steps: Vec<(u16, u32)>,
+}
+
+thread_local! {
+    /// The `max_step_weight` and `max_steps_per_block` of the version being simulated.
+    static LIMITS: Cell<(Weight, Option<u32>)> = const { Cell::new((Weight::MAX, None)) };
+}
+
+/// Stands for the steps of the version being simulated, counting down its estimated steps and
+/// consuming the whole `max_step_weight` of the version in each of them.
+#[derive(Default, Encode, Decode)]
+struct SimulatedStep {
+    steps_left: u32,
+}
+
+impl MigrationStep for SimulatedStep {
+    const VERSION: u16 = 0;
+    const MAX_CURSOR_LEN: u32 = 4;
+    fn max_step_weight() -> Weight {
+        LIMITS.with(|limits| limits.get().0)
+    }
+    fn max_steps_per_block() -> Option<u32> {
+        LIMITS.with(|limits| limits.get().1)
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        self.steps_left = self.steps_left.saturating_sub(1);
+        let finished = if self.steps_left == 0 {
+            IsFinished::Yes
+        } else {
+            IsFinished::No
+        };
+        (finished, Self::max_step_weight())
+    }
+}
+
+/// Returns the synthetic cursor of a version estimated to take `steps` steps.
+fn simulated_cursor(steps: u32) -> Cursor {
+    encode_cursor(&SimulatedStep { steps_left: steps })
+}
+
+/// Simulates the migration of `S` from `from_version` up to `last_version`, both included.
+///
+/// Every version runs through the same [`MigrateSequence::steps_checked`] logic `migrate` uses,
+/// against a synthetic cursor counting down the estimated steps of the version. `migrate_weight`
+/// is the fixed overhead of every `migrate` call. The returned plan stops as soon as all the
+/// versions are migrated, or after `input.max_blocks` blocks.
+pub fn simulate<S: MigrateSequence>(
+    from_version: u16,
+    last_version: u16,
+    migrate_weight: Weight,
+    input: &SimulationInput,
+) -> Vec<BlockPlan> {
+    let last_version = StorageVersion::new(last_version);
+    let mut version = Some(StorageVersion::new(from_version)).filter(|v| *v <= last_version);
+    let mut cursor = simulated_cursor(input.steps_for(from_version));
+    let mut plan = Vec::new();
+
+    for block in 0..input.max_blocks {
+        if version.is_none() {
+            break;
+        }
+        let mut remaining_weight = input.block_weight;
+        let mut steps_in_block = 0;
+        let mut block_plan = BlockPlan {
+            block,
+            steps: Vec::new(),
+        };
+
+        // every iteration stands for a `migrate` call of the `on_idle` loop
+        while let Some(in_progress_version) = version {
+            let mut weight_left = remaining_weight;
+            if weight_left.checked_reduce(migrate_weight).is_none() {
+                break;
+            }
+            LIMITS.with(|limits| {
+                limits.set((
+                    S::max_step_weight(in_progress_version),
+                    S::max_steps_per_block(in_progress_version),
+                ))
+            });
+            let result = <(SimulatedStep,)>::steps_checked(
+                StorageVersion::new(SimulatedStep::VERSION),
+                &cursor,
+                &mut weight_left,
+                steps_in_block,
+                &mut |_| {},
+            )
+            .expect("The synthetic cursor is always valid; qed");
+            remaining_weight = weight_left;
+
+            let steps_done = result.steps_done();
+            steps_in_block = steps_in_block.saturating_add(steps_done);
+            if steps_done > 0 {
+                block_plan
+                    .steps
+                    .push((storage_version_number(in_progress_version), steps_done));
+            }
+            match result {
+                StepResult::Completed { .. } => {
+                    // the steps of the next version start from zero, like `MigrationStepsInBlock`
+                    steps_in_block = 0;
+                    version = next_version(in_progress_version)
+                        .filter(|version| *version <= last_version);
+                    if let Some(version) = version {
+                        cursor = simulated_cursor(input.steps_for(storage_version_number(version)));
+                    }
+                }
+                StepResult::InProgress {
+                    cursor: next,
+                    steps_done,
+                } => {
+                    cursor = next;
+                    // not even a step fits into the weight left
+                    if steps_done == 0 {
+                        break;
+                    }
+                }
+                // the version reached its cap of steps for this block
+                StepResult::Yielded { cursor: next, .. } => {
+                    cursor = next;
+                    break;
+                }
+                // the synthetic steps never fail
+                StepResult::Failed { .. } => break,
+            }
+        }
+        plan.push(block_plan);
+    }
+    plan
+}
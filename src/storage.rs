@@ -0,0 +1,47 @@
+//! Low-level storage helpers commonly needed by migration steps, e.g. to read the items of an old
+//! storage layout that is no longer declared in the pallet.
+//!
+//! The helpers of [`frame_support::migration`] are re-exported as is, while the typed wrappers
+//! take the pallet and item names as `&str` and address plain storage values, whose key has no
+//! hashed suffix.
+
+pub use frame_support::{
+    migration::{
+        clear_storage_prefix, get_storage_value, have_storage_value, move_pallet, move_prefix,
+        move_storage_from_pallet, put_storage_value, storage_iter, storage_key_iter,
+        take_storage_item, take_storage_value,
+    },
+    storage::{storage_prefix, unhashed},
+};
+use parity_scale_codec::{Decode, Encode};
+
+/// Returns the value of the plain storage `item` of `pallet`, if it exists and decodes as `T`.
+pub fn get_value<T: Decode>(pallet: &str, item: &str) -> Option<T> {
+    get_storage_value(pallet.as_bytes(), item.as_bytes(), &[])
+}
+
+/// Removes the value of the plain storage `item` of `pallet`, returning it if it decodes as `T`.
+pub fn take_value<T: Decode>(pallet: &str, item: &str) -> Option<T> {
+    take_storage_value(pallet.as_bytes(), item.as_bytes(), &[])
+}
+
+/// Stores `value` as the plain storage `item` of `pallet`.
+pub fn put_value<T: Encode>(pallet: &str, item: &str, value: T) {
+    put_storage_value(pallet.as_bytes(), item.as_bytes(), &[], value)
+}
+
+/// Whether the plain storage `item` of `pallet` exists.
+pub fn has_value(pallet: &str, item: &str) -> bool {
+    have_storage_value(pallet.as_bytes(), item.as_bytes(), &[])
+}
+
+/// Removes up to `limit` keys of the storage `item` of `pallet`, e.g. a map of an old layout,
+/// returning whether keys are left.
+///
+/// Removing the keys left in a later block only requires calling it again, as the removed keys
+/// are gone from the storage once the block is committed.
+pub fn clear_item(pallet: &str, item: &str, limit: u32) -> bool {
+    clear_storage_prefix(pallet.as_bytes(), item.as_bytes(), &[], Some(limit), None)
+        .maybe_cursor
+        .is_some()
+}
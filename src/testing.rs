@@ -0,0 +1,115 @@
+//! Helpers to check migration sequences in plain unit tests, and to set up the migration state of
+//! a pallet in tests with a mock runtime.
+
+use crate::{Cursor, MigrateSequence};
+#[cfg(feature = "test-utils")]
+use crate::{MigrateResult, PalletMigration};
+use frame_support::pallet_prelude::Weight;
+#[cfg(feature = "test-utils")]
+use frame_support::traits::StorageVersion;
+
+/// Runs the integrity checks of the pallet's `integrity_test` hook against `S`.
+///
+/// Panics if the migrations of `S` are not ordered by their versions with no gaps, if a step
+/// doesn't fit into the [`Cursor`], or if its `max_step_weight` is greater than
+/// `max_block_weight`. Every failed check of every step is reported at once.
+///
+/// `max_block_weight` should be the `max_block` of the runtime's `BlockWeights`, e.g.
+/// `Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 2, 5 * 1024 * 1024)` for a parachain with
+/// half a second of execution and a 5 MiB PoV.
+///
+/// ```ignore
+/// #[test]
+/// fn migrations_are_valid() {
+///     migratable::testing::check_sequence_integrity::<(v2::Migration<Test>, v3::Migration<Test>)>(
+///         Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 2, 5 * 1024 * 1024),
+///     );
+/// }
+/// ```
+pub fn check_sequence_integrity<S: MigrateSequence>(max_block_weight: Weight) {
+    // evaluating the range panics if the versions have gaps
+    let _ = S::VERSION_RANGE;
+    S::integrity_test(max_block_weight, Cursor::bound());
+}
+
+/// The operations on the migrations of a pallet that are only meant for tests.
+///
+/// Implemented by the `Migration` struct generated by the `pallet` macro, as long as the
+/// `test-utils` feature of this crate is enabled.
+#[cfg(feature = "test-utils")]
+pub trait PalletMigrationTestUtils: PalletMigration {
+    /// Runs the migration in progress with up to `weight_limit`, like the generated `migrate`
+    /// call, returning its result and the weight consumed.
+    fn migrate(weight_limit: Weight) -> (MigrateResult, Weight);
+
+    /// Sets the on-chain storage version to `to`, with the migration of the next version, if any,
+    /// in progress from its first step, as if the node restarted right after completing `to`.
+    fn rewind(to: StorageVersion);
+}
+
+/// Rewinds the migrations of the pallet of `M` to `to`, to re-run them from there and check that
+/// they are idempotent and resumable.
+///
+/// The on-chain storage version is set to `to`, and the migration of the next version, if any, is
+/// put in progress from its first step, as if the node restarted right after completing `to`. The
+/// progress of the previous run is cleared, while its storage changes are kept.
+///
+/// ```ignore
+/// #[test]
+/// fn migration_is_idempotent() {
+///     new_test_ext().execute_with(|| {
+///         run_migrations();
+///         migratable::testing::rewind::<Migration<Test>>(StorageVersion::new(1));
+///         run_migrations();
+///         assert_eq!(Ledger::<Test>::get(ALICE), expected_ledger());
+///     });
+/// }
+/// ```
+#[cfg(feature = "test-utils")]
+pub fn rewind<M: PalletMigrationTestUtils>(to: StorageVersion) {
+    M::rewind(to);
+}
+
+/// Drives the migrations of the pallet of `M`, one `migrate` call of `per_block_weight` per
+/// simulated block, and panics unless they complete within `max_blocks`.
+///
+/// A regression guard on the pacing of the migrations, e.g. against a step whose batch size
+/// shrinks. It also panics if no migration is in progress, or if a call fails.
+///
+/// ```ignore
+/// #[test]
+/// fn migration_completes_in_ten_blocks() {
+///     new_test_ext().execute_with(|| {
+///         populate_ledgers(1_000);
+///         Migration::<Test>::on_runtime_upgrade();
+///         migratable::testing::assert_migration_completes_in::<Migration<Test>>(
+///             10,
+///             Weight::from_parts(WEIGHT_REF_TIME_PER_SECOND / 10, 512 * 1024),
+///         );
+///     });
+/// }
+/// ```
+#[cfg(feature = "test-utils")]
+pub fn assert_migration_completes_in<M: PalletMigrationTestUtils>(
+    max_blocks: u32,
+    per_block_weight: Weight,
+) {
+    let name = M::pallet_name();
+    for block in 1..=max_blocks {
+        match M::migrate(per_block_weight).0 {
+            MigrateResult::Completed => return,
+            MigrateResult::NoMigrationInProgress => {
+                panic!("{name}: No migration in progress at simulated block {block}")
+            }
+            MigrateResult::Failed { reason } => {
+                panic!("{name}: Migration failed at simulated block {block}: {reason}")
+            }
+            _ => {}
+        }
+    }
+    panic!(
+        "{name}: Migrations not completed within {max_blocks} blocks of {per_block_weight:?}, \
+         status: {:?}",
+        M::status(),
+    );
+}
@@ -0,0 +1,101 @@
+//! Helpers for the `try-runtime` checks of migration steps.
+
+use frame_support::{pallet_prelude::Encode, sp_io::hashing::blake2_256};
+use parity_scale_codec::{Codec, Decode};
+use sp_runtime::TryRuntimeError;
+use sp_std::prelude::*;
+
+/// A fixed-size fingerprint of a set of items, to compare the state before and after a migration
+/// without keeping every item in memory.
+///
+/// Items are folded one at a time, e.g. while iterating a map in
+/// [`MigrationStep::pre_upgrade_step`](crate::MigrationStep::pre_upgrade_step), and the encoded
+/// fingerprint is returned as the pre-upgrade state. The same items are then folded again in
+/// [`MigrationStep::post_upgrade_step`](crate::MigrationStep::post_upgrade_step) and both
+/// fingerprints are compared.
+///
+/// The fingerprint doesn't depend on the order the items are folded in, as migrated maps are
+/// usually iterated in a different order, but folding the same item twice cancels it out.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct RollingFingerprint {
+    hash: [u8; 32],
+    count: u64,
+}
+
+impl RollingFingerprint {
+    /// Returns the fingerprint of an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Folds `item` into the fingerprint.
+    pub fn fold<E: Encode>(&mut self, item: &E) {
+        let item_hash = item.using_encoded(blake2_256);
+        self.hash
+            .iter_mut()
+            .zip(item_hash)
+            .for_each(|(byte, item_byte)| *byte ^= item_byte);
+        self.count = self.count.saturating_add(1);
+    }
+
+    /// Returns the number of items folded so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+/// Typed `try-runtime` checks of a migration step, passing a decoded state from the pre-checks to
+/// the post-checks instead of raw bytes.
+///
+/// Associated type defaults being unstable, the typed checks live in this separate trait, and are
+/// wired into the byte-level checks of [`MigrationStep`](crate::MigrationStep) with
+/// [`pre_upgrade_step`] and [`post_upgrade_step`]:
+///
+/// ```ignore
+/// impl<T: Config> TypedUpgradeChecks for Migration<T> {
+///     type PreState = u32;
+///
+///     fn pre_upgrade_typed() -> Result<u32, TryRuntimeError> {
+///         Ok(v1::Ledger::<T>::iter().count() as u32)
+///     }
+///
+///     fn post_upgrade_typed(count: u32) -> Result<(), TryRuntimeError> {
+///         ensure!(Ledger::<T>::iter().count() as u32 == count, "Ledgers were lost");
+///         Ok(())
+///     }
+/// }
+///
+/// impl<T: Config> MigrationStep for Migration<T> {
+///     // -- snip --
+///
+///     fn pre_upgrade_step() -> Result<Vec<u8>, TryRuntimeError> {
+///         migratable::try_runtime::pre_upgrade_step::<Self>()
+///     }
+///
+///     fn post_upgrade_step(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+///         migratable::try_runtime::post_upgrade_step::<Self>(state)
+///     }
+/// }
+/// ```
+pub trait TypedUpgradeChecks {
+    /// The state passed from the pre-checks to the post-checks.
+    type PreState: Codec;
+
+    /// Execute some pre-checks prior to running the first step of the migration.
+    fn pre_upgrade_typed() -> Result<Self::PreState, TryRuntimeError>;
+
+    /// Execute some post-checks after running the last step of the migration.
+    fn post_upgrade_typed(state: Self::PreState) -> Result<(), TryRuntimeError>;
+}
+
+/// Runs the typed pre-checks of `M`, encoding their state.
+pub fn pre_upgrade_step<M: TypedUpgradeChecks>() -> Result<Vec<u8>, TryRuntimeError> {
+    M::pre_upgrade_typed().map(|state| state.encode())
+}
+
+/// Decodes the state of the pre-checks of `M` and runs its typed post-checks.
+pub fn post_upgrade_step<M: TypedUpgradeChecks>(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+    let state = M::PreState::decode(&mut &state[..])
+        .map_err(|_| "Failed to decode the pre-upgrade state")?;
+    M::post_upgrade_typed(state)
+}
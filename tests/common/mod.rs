@@ -0,0 +1,106 @@
+//! Fixtures shared by the tests: a migration step completing at once and a mock runtime with a
+//! pallet using the generated migration code.
+#![allow(dead_code)]
+
+use frame_support::weights::Weight;
+use migratable::{IsFinished, MigrationStep};
+use parity_scale_codec::{Decode, Encode};
+
+/// Migrates to `VERSION` in a single step weighing nothing.
+#[derive(Default, Encode, Decode)]
+pub struct MigrateTo<const VERSION: u16>;
+
+impl<const VERSION: u16> MigrationStep for MigrateTo<VERSION> {
+    const VERSION: u16 = VERSION;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+}
+
+#[migratable::pallet(calls)]
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
+
+    #[migratable::config]
+    #[pallet::config]
+    pub trait Config: frame_system::Config {}
+
+    // the migrations of each runtime stop at the last version of their sequence
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(5);
+
+    #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T>(_);
+
+    #[migratable::hooks]
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+}
+
+/// Declares a `Test` runtime with the pallet of the mock, or the given one, migrated by
+/// `$migrations`, and a `new_test_ext` function to run the tests in, starting at block 1.
+macro_rules! mock_runtime {
+    ($migrations:ty) => {
+        mock_runtime!($migrations, crate::common::pallet);
+    };
+    ($migrations:ty, $($pallet:tt)+) => {
+        type Block = frame_system::mocking::MockBlock<Test>;
+        type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+
+        frame_support::construct_runtime!(
+            pub enum Test where
+                Block = Block,
+                NodeBlock = Block,
+                UncheckedExtrinsic = UncheckedExtrinsic,
+            {
+                System: frame_system,
+                Example: $($pallet)+,
+            }
+        );
+
+        impl frame_system::Config for Test {
+            type BaseCallFilter = frame_support::traits::Everything;
+            type BlockWeights = ();
+            type BlockLength = ();
+            type DbWeight = frame_support::weights::constants::RocksDbWeight;
+            type RuntimeOrigin = RuntimeOrigin;
+            type RuntimeCall = RuntimeCall;
+            type Index = u64;
+            type BlockNumber = u64;
+            type Hash = sp_runtime::testing::H256;
+            type Hashing = sp_runtime::traits::BlakeTwo256;
+            type AccountId = u64;
+            type Lookup = sp_runtime::traits::IdentityLookup<u64>;
+            type Header = sp_runtime::testing::Header;
+            type RuntimeEvent = RuntimeEvent;
+            type BlockHashCount = frame_support::traits::ConstU64<250>;
+            type Version = ();
+            type PalletInfo = PalletInfo;
+            type AccountData = ();
+            type OnNewAccount = ();
+            type OnKilledAccount = ();
+            type SystemWeightInfo = ();
+            type SS58Prefix = ();
+            type OnSetCode = ();
+            type MaxConsumers = frame_support::traits::ConstU32<16>;
+        }
+
+        impl $($pallet)+::Config for Test {
+            type Migrations = $migrations;
+            type MigrationObserver = ();
+        }
+
+        fn new_test_ext() -> frame_support::sp_io::TestExternalities {
+            let mut ext = frame_support::sp_io::TestExternalities::default();
+            ext.execute_with(|| System::set_block_number(1));
+            ext
+        }
+    };
+}
+
+#[allow(unused_imports)]
+pub(crate) use mock_runtime;
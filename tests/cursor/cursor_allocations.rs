@@ -0,0 +1,93 @@
+use frame_support::{traits::StorageVersion, weights::Weight};
+use migratable::{IsFinished, MigrateSequence, MigrationStep, StepResult};
+use parity_scale_codec::{Decode, Encode};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+thread_local! {
+    /// The allocations and reallocations made by the current thread.
+    static ALLOCATIONS: Cell<(u32, u32)> = const { Cell::new((0, 0)) };
+}
+
+/// Counts the allocations of each thread, so that the tests running in parallel don't interfere.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| {
+            let (allocs, reallocs) = count.get();
+            count.set((allocs + 1, reallocs));
+        });
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| {
+            let (allocs, reallocs) = count.get();
+            count.set((allocs, reallocs + 1));
+        });
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the allocations and reallocations made by `f`.
+fn allocations(f: impl FnOnce()) -> (u32, u32) {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    let after = ALLOCATIONS.with(Cell::get);
+    (after.0 - before.0, after.1 - before.1)
+}
+
+const STEP_WEIGHT: Weight = Weight::from_parts(10, 0);
+
+/// Holds a position whose encoding doesn't allocate, so that only the cursor does.
+#[derive(Default, Encode, Decode)]
+struct MigrateV1 {
+    position: u64,
+    last_key: [u8; 32],
+}
+
+impl MigrationStep for MigrateV1 {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 40;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(STEP_WEIGHT);
+    fn step(&mut self) -> (IsFinished, Weight) {
+        self.position += 1;
+        self.last_key = [self.position as u8; 32];
+        (IsFinished::No, STEP_WEIGHT)
+    }
+}
+
+type Migrations = (MigrateV1,);
+
+#[test]
+fn cursor_is_encoded_in_a_single_allocation() {
+    let v1 = StorageVersion::new(1);
+    let mut cursor = Migrations::new(v1);
+    for _ in 0..10 {
+        let (allocs, reallocs) = allocations(|| {
+            let mut weight_left = STEP_WEIGHT.mul(100);
+            let result = Migrations::steps_checked(v1, &cursor, &mut weight_left, 0, &mut |_| {});
+            let Ok(StepResult::InProgress {
+                cursor: next,
+                steps_done: 100,
+            }) = result
+            else {
+                panic!("Unexpected result {result:?}");
+            };
+            cursor = next;
+        });
+        // sized from the size hint of the step, with no intermediate buffer
+        assert_eq!((allocs, reallocs), (1, 0));
+    }
+    assert_eq!(cursor.len(), 3 + 40);
+}
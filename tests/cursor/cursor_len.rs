@@ -0,0 +1,53 @@
+use frame_support::weights::Weight;
+use migratable::{IntegrityError, IsFinished, MigrateSequence, MigrationStep};
+use parity_scale_codec::{Decode, Encode, MaxEncodedLen};
+
+#[derive(Default, Encode, Decode, MaxEncodedLen)]
+struct MigrateV2 {
+    last_key: Option<u64>,
+}
+
+impl MigrationStep for MigrateV2 {
+    const VERSION: u16 = 2;
+    const MAX_CURSOR_LEN: u32 = 9;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn encoded_len_bound() -> Option<usize> {
+        Some(Self::max_encoded_len())
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+}
+
+// declares the length of a `u32` key, while it holds a `u64` one
+#[derive(Default, Encode, Decode, MaxEncodedLen)]
+struct MigrateV3 {
+    last_key: Option<u64>,
+}
+
+impl MigrationStep for MigrateV3 {
+    const VERSION: u16 = 3;
+    const MAX_CURSOR_LEN: u32 = 5;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn encoded_len_bound() -> Option<usize> {
+        Some(Self::max_encoded_len())
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+}
+
+const MAX_BLOCK_WEIGHT: Weight = Weight::from_parts(1_000_000, 1_000_000);
+
+#[test]
+fn max_cursor_len_is_checked_against_the_encoded_len_bound() {
+    assert!(<(MigrateV2,)>::integrity_check(MAX_BLOCK_WEIGHT, 1024).is_empty());
+    assert_eq!(
+        <(MigrateV2, MigrateV3)>::integrity_check(MAX_BLOCK_WEIGHT, 1024),
+        vec![IntegrityError::CursorLenTooLow {
+            version: 3,
+            declared: 5,
+            bound: 9
+        }],
+    );
+}
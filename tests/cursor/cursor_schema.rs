@@ -0,0 +1,63 @@
+use frame_support::{traits::StorageVersion, weights::Weight};
+use migratable::{IsFinished, MigrateError, MigrateSequence, MigrationStep};
+use parity_scale_codec::{Decode, Encode};
+
+/// Used to hold the bare last key, before it became optional with the schema 1.
+#[derive(Default, Encode, Decode)]
+struct MigrateV2 {
+    last_key: Option<u32>,
+}
+
+impl MigrationStep for MigrateV2 {
+    const VERSION: u16 = 2;
+    const MAX_CURSOR_LEN: u32 = 5;
+    const CURSOR_SCHEMA: u8 = 1;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+    fn describe_position(&self) -> String {
+        format!("{:?}", self.last_key)
+    }
+    fn migrate_cursor(old_schema: u8, mut bytes: &[u8]) -> Result<Self, parity_scale_codec::Error> {
+        match old_schema {
+            0 => Ok(Self {
+                last_key: Some(u32::decode(&mut bytes)?),
+            }),
+            _ => Err("Unknown cursor schema".into()),
+        }
+    }
+}
+
+type Migrations = (MigrateV2,);
+
+/// Returns a cursor of `MigrateV2` with the given schema and encoded step.
+fn cursor(schema: u8, step: impl Encode) -> Vec<u8> {
+    let mut cursor = 2u16.encode();
+    cursor.push(schema);
+    step.encode_to(&mut cursor);
+    cursor
+}
+
+#[test]
+fn stale_cursor_is_migrated() {
+    let v2 = StorageVersion::new(2);
+    let stale = cursor(0, 42u32);
+    assert!(Migrations::validate_cursor(v2, &stale));
+    assert_eq!(Migrations::describe_position(v2, &stale), Ok("Some(42)".into()));
+
+    // the current schema is decoded as is
+    let current = cursor(1, Some(7u32));
+    assert_eq!(Migrations::describe_position(v2, &current), Ok("Some(7)".into()));
+}
+
+#[test]
+fn unknown_schema_is_rejected() {
+    let v2 = StorageVersion::new(2);
+    let unknown = cursor(5, 42u32);
+    assert!(!Migrations::validate_cursor(v2, &unknown));
+    assert_eq!(
+        Migrations::steps_checked(v2, &unknown, &mut Weight::MAX, 0, &mut |_| {}),
+        Err(MigrateError::CursorDecode),
+    );
+}
@@ -0,0 +1,46 @@
+use frame_support::{traits::StorageVersion, weights::Weight};
+use migratable::{IsFinished, MigrateError, MigrateSequence, MigrationStep};
+use parity_scale_codec::{Decode, Encode};
+
+#[derive(Default, Encode, Decode)]
+struct MigrateV2 {
+    last_key: Option<u32>,
+}
+
+impl MigrationStep for MigrateV2 {
+    const VERSION: u16 = 2;
+    const MAX_CURSOR_LEN: u32 = 5;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+    fn describe_position(&self) -> String {
+        format!("{:?}", self.last_key)
+    }
+}
+
+type Migrations = (MigrateV2,);
+
+#[test]
+fn baseline_cursor_is_upgraded() {
+    let v2 = StorageVersion::new(2);
+    // a cursor persisted before the header was introduced: the bare encoded step
+    let legacy = MigrateV2 { last_key: Some(42) }.encode();
+    assert!(!Migrations::validate_cursor(v2, &legacy));
+
+    let cursor = Migrations::upgrade_legacy_cursor(v2, &legacy).unwrap();
+    assert!(Migrations::validate_cursor(v2, &cursor));
+    assert_eq!(Migrations::describe_position(v2, &cursor), Ok("Some(42)".into()));
+}
+
+#[test]
+fn invalid_legacy_cursor_is_rejected() {
+    assert_eq!(
+        Migrations::upgrade_legacy_cursor(StorageVersion::new(2), &[2]),
+        Err(MigrateError::CursorDecode),
+    );
+    assert_eq!(
+        Migrations::upgrade_legacy_cursor(StorageVersion::new(3), &[0]),
+        Err(MigrateError::UnsupportedVersion(3)),
+    );
+}
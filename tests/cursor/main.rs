@@ -0,0 +1,12 @@
+//! The cursor of the migration in progress: its encoding, length bound and schema upgrades, and
+//! the migration of the other persisted progress.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod cursor_allocations;
+mod cursor_len;
+mod cursor_schema;
+mod legacy_cursor;
+mod map_migration;
+mod steps_done_counter;
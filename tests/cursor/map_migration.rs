@@ -0,0 +1,64 @@
+use frame_support::{storage_alias, weights::Weight, Twox64Concat};
+use migratable::{IsFinished, MapMigration, MigrationStep};
+use parity_scale_codec::{Decode, Encode};
+
+const STEP_WEIGHT: Weight = Weight::from_parts(10, 0);
+
+#[storage_alias]
+type OldLedger = StorageMap<Example, Twox64Concat, u32, u32>;
+
+#[storage_alias]
+type Ledger = StorageMap<Example, Twox64Concat, u32, u64>;
+
+/// Doubles the balances, dropping the empty ones.
+fn into_ledger(key: u32, value: u32) -> Option<(u32, u64)> {
+    (value > 0).then(|| (key, u64::from(value) * 2))
+}
+
+#[derive(Encode, Decode, MapMigration)]
+#[map_migration(
+    version = 2,
+    old = OldLedger,
+    new = Ledger,
+    transform = into_ledger,
+    weight = STEP_WEIGHT,
+    max_key_len = 80,
+)]
+struct MigrateLedger {
+    last_key: Option<Vec<u8>>,
+}
+
+#[test]
+fn cursor_fits_the_longest_key() {
+    // `Option` tag, two bytes of compact length and the key
+    assert_eq!(MigrateLedger::MAX_CURSOR_LEN, 83);
+}
+
+#[test]
+fn entries_are_migrated_one_per_step() {
+    frame_support::sp_io::TestExternalities::default().execute_with(|| {
+        OldLedger::insert(1, 10);
+        OldLedger::insert(2, 0);
+        OldLedger::insert(3, 30);
+
+        let mut migration = MigrateLedger::default();
+        let mut steps = 0;
+        loop {
+            let (finished, weight) = migration.step();
+            assert_eq!(weight, STEP_WEIGHT);
+            steps += 1;
+            if matches!(finished, IsFinished::Yes) {
+                break;
+            }
+            // resumed from its cursor, like across blocks
+            migration = MigrateLedger::decode(&mut &migration.encode()[..]).unwrap();
+        }
+
+        // one step per entry, and a last one finding no entry left
+        assert_eq!(steps, 4);
+        assert_eq!(OldLedger::iter().count(), 0);
+        assert_eq!(Ledger::get(1), Some(20));
+        assert_eq!(Ledger::get(2), None);
+        assert_eq!(Ledger::get(3), Some(60));
+    });
+}
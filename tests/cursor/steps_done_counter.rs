@@ -0,0 +1,69 @@
+use frame_support::{
+    storage::{migration, unhashed},
+    traits::{CrateVersion, OnRuntimeUpgrade, PalletInfoAccess},
+    weights::{constants::RocksDbWeight, RuntimeDbWeight},
+};
+use migratable::MigrateStepsDoneToU64;
+use parity_scale_codec::Encode;
+
+struct MyPallet;
+
+impl PalletInfoAccess for MyPallet {
+    fn index() -> usize {
+        0
+    }
+    fn name() -> &'static str {
+        "MyPallet"
+    }
+    fn module_name() -> &'static str {
+        "my_pallet"
+    }
+    fn crate_version() -> CrateVersion {
+        CrateVersion::new(1, 0, 0)
+    }
+}
+
+type Migration = MigrateStepsDoneToU64<MyPallet, RocksDbWeight>;
+
+fn key() -> [u8; 32] {
+    frame_support::storage::storage_prefix(b"MyPallet", b"MigrationStepsDone")
+}
+
+fn steps_done() -> Option<u64> {
+    migration::get_storage_value::<u64>(b"MyPallet", b"MigrationStepsDone", &[])
+}
+
+fn db_weight() -> RuntimeDbWeight {
+    <RocksDbWeight as frame_support::traits::Get<_>>::get()
+}
+
+#[test]
+fn legacy_counter_is_widened() {
+    frame_support::sp_io::TestExternalities::default().execute_with(|| {
+        unhashed::put_raw(&key(), &u32::MAX.encode());
+        // the legacy counter doesn't decode as a `u64`
+        assert_eq!(steps_done(), None);
+
+        assert_eq!(Migration::on_runtime_upgrade(), db_weight().reads_writes(1, 1));
+        assert_eq!(steps_done(), Some(u32::MAX as u64));
+    });
+}
+
+#[test]
+fn widened_counter_is_untouched() {
+    frame_support::sp_io::TestExternalities::default().execute_with(|| {
+        let steps = u32::MAX as u64 + 1;
+        unhashed::put(&key(), &steps);
+
+        assert_eq!(Migration::on_runtime_upgrade(), db_weight().reads(1));
+        assert_eq!(steps_done(), Some(steps));
+    });
+}
+
+#[test]
+fn missing_counter_is_untouched() {
+    frame_support::sp_io::TestExternalities::default().execute_with(|| {
+        assert_eq!(Migration::on_runtime_upgrade(), db_weight().reads(1));
+        assert_eq!(steps_done(), None);
+    });
+}
@@ -0,0 +1,76 @@
+use frame_support::traits::{Contains, OnRuntimeUpgrade, StorageVersion};
+use migratable::filter::MigrationCallFilter;
+
+use crate::common::{mock_runtime, MigrateTo};
+
+#[migratable::pallet(calls)]
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
+
+    #[migratable::config]
+    #[pallet::config]
+    pub trait Config: frame_system::Config {}
+
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+    #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T>(_);
+
+    #[migratable::hooks]
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {}
+
+    #[pallet::call]
+    impl<T: Config> Pallet<T> {
+        #[pallet::call_index(0)]
+        #[pallet::weight(Weight::zero())]
+        pub fn pause(origin: OriginFor<T>) -> DispatchResult {
+            ensure_root(origin)?;
+            Ok(())
+        }
+
+        #[pallet::call_index(1)]
+        #[pallet::weight(Weight::zero())]
+        pub fn set_value(origin: OriginFor<T>, _value: u32) -> DispatchResult {
+            ensure_signed(origin)?;
+            Ok(())
+        }
+    }
+}
+
+mock_runtime!((MigrateTo<1>,), pallet);
+
+struct AllowPause;
+
+impl Contains<RuntimeCall> for AllowPause {
+    fn contains(call: &RuntimeCall) -> bool {
+        matches!(call, RuntimeCall::Example(pallet::Call::pause {}))
+    }
+}
+
+type Filter = MigrationCallFilter<(pallet::Migration<Test>,), AllowPause>;
+
+#[test]
+fn calls_of_the_migrating_pallet_are_filtered_out() {
+    let set_value = RuntimeCall::Example(pallet::Call::set_value { _value: 1 });
+    let pause = RuntimeCall::Example(pallet::Call::pause {});
+    let migrate = RuntimeCall::Example(pallet::Call::migrate {
+        weight_limit: frame_support::weights::Weight::MAX,
+    });
+    let remark = RuntimeCall::System(frame_system::Call::remark { remark: Vec::new() });
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Example>();
+        assert!(Filter::contains(&set_value));
+
+        pallet::Migration::<Test, false>::on_runtime_upgrade();
+        assert!(!Filter::contains(&set_value));
+        // the allowed and generated calls, and the calls of the other pallets, are let through
+        assert!(Filter::contains(&pause));
+        assert!(Filter::contains(&migrate));
+        assert!(Filter::contains(&remark));
+    });
+}
@@ -0,0 +1,53 @@
+use frame_support::weights::Weight;
+use migratable::{IntegrityError, MigrationStatus, PalletMigration};
+
+/// A pallet whose migrations fail the given checks.
+struct Pallet<const I: usize>;
+
+impl<const I: usize> PalletMigration for Pallet<I> {
+    fn pallet_name() -> &'static str {
+        ["PalletA", "PalletB"][I]
+    }
+
+    fn status() -> MigrationStatus {
+        MigrationStatus::Completed
+    }
+
+    fn can_start() -> bool {
+        false
+    }
+
+    fn start_weight() -> Weight {
+        Weight::zero()
+    }
+
+    fn migrate_idle(_remaining_weight: &mut Weight) -> Weight {
+        Weight::zero()
+    }
+
+    fn integrity_check() -> Vec<IntegrityError> {
+        match I {
+            0 => Vec::new(),
+            _ => vec![
+                IntegrityError::MissingMaxStepWeight { version: 2 },
+                IntegrityError::StorageVersionMismatch {
+                    current: 2,
+                    last: 3,
+                },
+            ],
+        }
+    }
+}
+
+#[test]
+fn passing_pallets_dont_panic() {
+    migratable::integrity_test_all!(Pallet<0>);
+}
+
+#[test]
+#[should_panic(expected = "Integrity checks failed:\n\
+    - PalletB: Migration 2 must define either MAX_STEP_WEIGHT or max_step_weight\n\
+    - PalletB: The current storage version 2 should match the last version 3 of `Config::Migrations`")]
+fn failed_checks_of_every_pallet_are_reported() {
+    migratable::integrity_test_all!(Pallet<0>, Pallet<1>,);
+}
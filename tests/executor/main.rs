@@ -0,0 +1,9 @@
+//! The runtime level handling of the migrations of several pallets: the executors, the call
+//! filter and the integrity checks.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod call_filter;
+mod integrity_test_all;
+mod ordered;
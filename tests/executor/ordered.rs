@@ -0,0 +1,165 @@
+use frame_support::{
+    traits::{OnIdle, OnRuntimeUpgrade},
+    weights::Weight,
+};
+use migratable::{executor::MigrationExecutor, IntegrityError, MigrationStatus, PalletMigration};
+use std::cell::RefCell;
+
+const STEP_WEIGHT: Weight = Weight::from_parts(10, 0);
+const START_WEIGHT: Weight = Weight::from_parts(5, 0);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stage {
+    NotStarted,
+    InProgress { steps_left: u32 },
+    Completed,
+    Unsupported,
+}
+
+thread_local! {
+    // the stage of the migrations of `Pallet<0>` and `Pallet<1>`
+    static STAGES: RefCell<[Stage; 2]> = const { RefCell::new([Stage::NotStarted; 2]) };
+}
+
+fn stage(pallet: usize) -> Stage {
+    STAGES.with(|stages| stages.borrow()[pallet])
+}
+
+fn set_stage(pallet: usize, stage: Stage) {
+    STAGES.with(|stages| stages.borrow_mut()[pallet] = stage);
+}
+
+/// A pallet whose migration takes 2 steps.
+struct Pallet<const I: usize>;
+
+impl<const I: usize> PalletMigration for Pallet<I> {
+    fn pallet_name() -> &'static str {
+        ["PalletA", "PalletB"][I]
+    }
+
+    fn status() -> MigrationStatus {
+        match stage(I) {
+            Stage::NotStarted | Stage::Unsupported => MigrationStatus::NotStarted,
+            Stage::InProgress { .. } => MigrationStatus::InProgress {
+                version: 2,
+                cursor_len: 0,
+            },
+            Stage::Completed => MigrationStatus::Completed,
+        }
+    }
+
+    // the upgrade from the on-chain version is not supported by its migrations
+    fn can_start() -> bool {
+        stage(I) != Stage::Unsupported
+    }
+
+    fn start_weight() -> Weight {
+        START_WEIGHT
+    }
+
+    fn migrate_idle(remaining_weight: &mut Weight) -> Weight {
+        let mut weight = Weight::zero();
+        while let Stage::InProgress { steps_left } = stage(I) {
+            if remaining_weight.any_lt(STEP_WEIGHT) {
+                break;
+            }
+            remaining_weight.saturating_reduce(STEP_WEIGHT);
+            weight.saturating_accrue(STEP_WEIGHT);
+            set_stage(
+                I,
+                match steps_left - 1 {
+                    0 => Stage::Completed,
+                    steps_left => Stage::InProgress { steps_left },
+                },
+            );
+        }
+        weight
+    }
+
+    fn integrity_check() -> Vec<IntegrityError> {
+        Vec::new()
+    }
+}
+
+impl<const I: usize> OnRuntimeUpgrade for Pallet<I> {
+    fn on_runtime_upgrade() -> Weight {
+        if stage(I) == Stage::NotStarted {
+            set_stage(I, Stage::InProgress { steps_left: 2 });
+        }
+        START_WEIGHT
+    }
+}
+
+migratable::ordered_migrations! {
+    type Ordered = (Pallet<0>, Pallet<1>);
+}
+
+fn on_idle<E: OnIdle<u32>>(steps: u64) -> Weight {
+    E::on_idle(1, STEP_WEIGHT.saturating_mul(steps))
+}
+
+#[test]
+fn executor_drives_the_pallets_in_order() {
+    Pallet::<0>::on_runtime_upgrade();
+    Pallet::<1>::on_runtime_upgrade();
+
+    // the first pallet takes the whole weight
+    assert_eq!(
+        on_idle::<MigrationExecutor<(Pallet<0>, Pallet<1>)>>(2),
+        STEP_WEIGHT.saturating_mul(2)
+    );
+    assert_eq!(stage(0), Stage::Completed);
+    assert_eq!(stage(1), Stage::InProgress { steps_left: 2 });
+
+    assert_eq!(
+        on_idle::<MigrationExecutor<(Pallet<0>, Pallet<1>)>>(3),
+        STEP_WEIGHT.saturating_mul(2)
+    );
+    assert_eq!(stage(1), Stage::Completed);
+}
+
+#[test]
+fn ordered_migrations_start_once_the_previous_pallet_completes() {
+    assert_eq!(Ordered::on_runtime_upgrade(), START_WEIGHT);
+    assert_eq!(stage(0), Stage::InProgress { steps_left: 2 });
+    assert_eq!(stage(1), Stage::NotStarted);
+
+    // the second pallet waits for the first one
+    on_idle::<MigrationExecutor<Ordered>>(1);
+    assert_eq!(stage(0), Stage::InProgress { steps_left: 1 });
+    assert_eq!(stage(1), Stage::NotStarted);
+
+    // and is started within the same block the first one completes
+    assert_eq!(on_idle::<MigrationExecutor<Ordered>>(2), STEP_WEIGHT + START_WEIGHT);
+    assert_eq!(stage(0), Stage::Completed);
+    assert_eq!(stage(1), Stage::InProgress { steps_left: 2 });
+}
+
+#[test]
+fn ordered_migrations_wait_for_the_start_weight() {
+    set_stage(0, Stage::Completed);
+
+    // not even the start fits
+    assert_eq!(
+        MigrationExecutor::<Ordered>::on_idle(1, START_WEIGHT - Weight::from_parts(1, 0)),
+        Weight::zero()
+    );
+    assert_eq!(stage(1), Stage::NotStarted);
+
+    assert_eq!(MigrationExecutor::<Ordered>::on_idle(1, START_WEIGHT), START_WEIGHT);
+    assert_eq!(stage(1), Stage::InProgress { steps_left: 2 });
+}
+
+#[test]
+fn unsupported_upgrade_does_not_hold_back_the_next_pallets() {
+    set_stage(0, Stage::Unsupported);
+
+    assert_eq!(Ordered::on_runtime_upgrade(), START_WEIGHT.saturating_mul(2));
+    assert_eq!(stage(0), Stage::Unsupported);
+    assert_eq!(stage(1), Stage::InProgress { steps_left: 2 });
+
+    // nor is it started again from `on_idle`
+    assert_eq!(on_idle::<MigrationExecutor<Ordered>>(2), STEP_WEIGHT.saturating_mul(2));
+    assert_eq!(stage(0), Stage::Unsupported);
+    assert_eq!(stage(1), Stage::Completed);
+}
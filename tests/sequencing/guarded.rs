@@ -0,0 +1,67 @@
+use frame_support::{traits::StorageVersion, weights::Weight};
+use migratable::{GuardStep, Guarded, IsFinished, MigrateSequence, MigrationStep, StepResult};
+use parity_scale_codec::{Decode, Encode};
+use std::cell::Cell;
+
+use crate::common::MigrateTo;
+
+thread_local! {
+    static READY: Cell<bool> = const { Cell::new(false) };
+}
+
+struct IsReady;
+
+impl GuardStep for IsReady {
+    fn check() -> Result<(), &'static str> {
+        if READY.with(Cell::get) {
+            Ok(())
+        } else {
+            Err("Not ready")
+        }
+    }
+}
+
+#[derive(Default, Encode, Decode)]
+struct MigrateV3;
+
+impl MigrationStep for MigrateV3 {
+    const VERSION: u16 = 3;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+    fn check_dependencies() -> Result<(), &'static str> {
+        Err("Pallet B not migrated")
+    }
+}
+
+type Migrations = Guarded<IsReady, (MigrateTo<2>, MigrateV3)>;
+
+#[test]
+fn guard_holds_back_the_first_version_only() {
+    READY.with(|ready| ready.set(false));
+    assert_eq!(Migrations::check_dependencies(StorageVersion::new(2)), Err("Not ready"));
+    // only the dependencies of the step are checked past the first version
+    assert_eq!(
+        Migrations::check_dependencies(StorageVersion::new(3)),
+        Err("Pallet B not migrated"),
+    );
+}
+
+#[test]
+fn passing_guard_forwards_to_the_sequence() {
+    READY.with(|ready| ready.set(true));
+    let v2 = StorageVersion::new(2);
+    assert_eq!(Migrations::check_dependencies(v2), Ok(()));
+    // the dependencies of the steps are still checked
+    assert_eq!(
+        Migrations::check_dependencies(StorageVersion::new(3)),
+        Err("Pallet B not migrated"),
+    );
+    assert_eq!(Migrations::VERSION_RANGE, (2, 3));
+    assert_eq!(
+        Migrations::steps(v2, &Migrations::new(v2), &mut Weight::MAX, &mut |_| {}),
+        StepResult::Completed { steps_done: 1 },
+    );
+}
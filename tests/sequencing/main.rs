@@ -0,0 +1,13 @@
+//! The sequencing of the versions and of the steps within them: no-op and skipped versions,
+//! partial sequences, guards, phases and parallel steps.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod guarded;
+mod noop;
+mod parallel;
+mod partial_sequence;
+mod phased;
+mod should_run;
+mod steps_done;
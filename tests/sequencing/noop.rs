@@ -0,0 +1,72 @@
+use frame_support::{
+    traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+    weights::Weight,
+};
+use migratable::{
+    weights::{SubstrateWeight, WeightInfo},
+    MigrateResult, MigrateSequence, NoopMigration,
+};
+
+use crate::common::{
+    mock_runtime,
+    pallet::{Migration, MigrationStartedInUpgrade},
+    MigrateTo,
+};
+
+type Migrations = (MigrateTo<2>, NoopMigration<3>, NoopMigration<4>, NoopMigration<5>);
+
+mock_runtime!(Migrations);
+
+#[test]
+fn trailing_noops_are_skipped_at_once() {
+    assert!(!Migrations::is_noop(StorageVersion::new(2)));
+    assert!(Migrations::is_noop(StorageVersion::new(3)));
+
+    // completing v2 completes the three noops after it
+    assert_eq!(
+        Migrations::skip_noops(StorageVersion::new(2), StorageVersion::new(5)),
+        StorageVersion::new(5),
+    );
+    // up to the target version
+    assert_eq!(
+        Migrations::skip_noops(StorageVersion::new(2), StorageVersion::new(4)),
+        StorageVersion::new(4),
+    );
+    // v2 must still be migrated
+    assert_eq!(
+        Migrations::skip_noops(StorageVersion::new(1), StorageVersion::new(5)),
+        StorageVersion::new(1),
+    );
+}
+
+#[test]
+fn trailing_noops_complete_in_the_same_migrate_call() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<Example>();
+        Migration::<Test, false>::on_runtime_upgrade();
+        assert!(Migration::<Test>::in_progress());
+        assert_eq!(MigrationStartedInUpgrade::<Test>::get(), Some(1));
+
+        let (result, _) = Migration::<Test>::migrate(Weight::MAX);
+        assert_eq!(result, MigrateResult::Completed);
+        assert_eq!(MigrationStartedInUpgrade::<Test>::get(), None);
+        assert_eq!(Example::on_chain_storage_version(), StorageVersion::new(5));
+        assert!(!Migration::<Test>::in_progress());
+
+        // nothing left for a second call
+        let (result, _) = Migration::<Test>::migrate(Weight::MAX);
+        assert_eq!(result, MigrateResult::NoMigrationInProgress);
+    });
+}
+
+#[test]
+fn unsupported_upgrade_starts_nothing() {
+    new_test_ext().execute_with(|| {
+        // v1 is not in the sequence anymore
+        StorageVersion::new(0).put::<Example>();
+        let weight = Migration::<Test, false>::on_runtime_upgrade();
+        assert_eq!(weight, SubstrateWeight::<Test>::on_runtime_upgrade_noop());
+        assert!(!Migration::<Test>::in_progress());
+        assert_eq!(Example::on_chain_storage_version(), StorageVersion::new(0));
+    });
+}
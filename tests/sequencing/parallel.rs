@@ -0,0 +1,141 @@
+use frame_support::{storage::unhashed, traits::StorageVersion, weights::Weight};
+use migratable::{IsFinished, MigrateSequence, MigrationStep, Parallel, StepResult};
+use parity_scale_codec::{Decode, Encode};
+
+const WEIGHT_A: Weight = Weight::from_parts(10, 0);
+const WEIGHT_B: Weight = Weight::from_parts(20, 0);
+const STEP_WEIGHT: Weight = Weight::from_parts(30, 0);
+
+// migrates 3 items, checkpointing after each of them
+#[derive(Default, Encode, Decode)]
+struct MigrateA {
+    done: u32,
+}
+
+impl MigrationStep for MigrateA {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 4;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(WEIGHT_A);
+    const TRANSACTIONAL: bool = true;
+    fn step(&mut self) -> (IsFinished, Weight) {
+        self.step_with_checkpoint(&mut |_| {})
+    }
+    fn step_with_checkpoint(&mut self, checkpoint: &mut dyn FnMut(&Self)) -> (IsFinished, Weight) {
+        self.done += 1;
+        unhashed::put(b"a", &self.done);
+        checkpoint(self);
+        let finished = if self.done == 3 {
+            IsFinished::Yes
+        } else {
+            IsFinished::No
+        };
+        (finished, WEIGHT_A)
+    }
+    fn describe_position(&self) -> String {
+        self.done.to_string()
+    }
+}
+
+// migrates a single item, and fails while `b:fail` is set. Its schema 0 stored a `u8`.
+#[derive(Default, Encode, Decode)]
+struct MigrateB {
+    done: u32,
+}
+
+impl MigrationStep for MigrateB {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 4;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(WEIGHT_B);
+    const CURSOR_SCHEMA: u8 = 1;
+    fn step(&mut self) -> (IsFinished, Weight) {
+        self.done += 1;
+        (IsFinished::Yes, WEIGHT_B)
+    }
+    fn try_step(&mut self) -> Result<(IsFinished, Weight), Weight> {
+        if unhashed::exists(b"b:fail") {
+            return Err(WEIGHT_B);
+        }
+        Ok(self.step())
+    }
+    fn describe_position(&self) -> String {
+        self.done.to_string()
+    }
+    fn migrate_cursor(
+        _old_schema: u8,
+        mut bytes: &[u8],
+    ) -> Result<Self, parity_scale_codec::Error> {
+        Ok(Self {
+            done: u8::decode(&mut bytes)?.into(),
+        })
+    }
+}
+
+type Migrations = (Parallel<MigrateA, MigrateB>,);
+
+const V1: StorageVersion = StorageVersion::new(1);
+
+fn run(cursor: &[u8], steps: u64, checkpoint: &mut dyn FnMut(migratable::Cursor)) -> StepResult {
+    let mut weight_left = STEP_WEIGHT.saturating_mul(steps);
+    Migrations::steps(V1, cursor, &mut weight_left, checkpoint)
+}
+
+#[test]
+fn parts_advance_side_by_side() {
+    assert!(<Parallel<MigrateA, MigrateB> as MigrationStep>::TRANSACTIONAL);
+    frame_support::sp_io::TestExternalities::default().execute_with(|| {
+        let StepResult::InProgress {
+            cursor,
+            steps_done: 2,
+        } = run(&Migrations::new(V1), 2, &mut |_| {})
+        else {
+            panic!("the migration should be in progress");
+        };
+        // `B` finished in the first step
+        assert_eq!(Migrations::describe_position(V1, &cursor), Ok("2; finished".into()));
+
+        assert_eq!(run(&cursor, 2, &mut |_| {}), StepResult::Completed { steps_done: 1 });
+        assert_eq!(unhashed::get::<u32>(b"a"), Some(3));
+    });
+}
+
+#[test]
+fn failed_part_rolls_back_both() {
+    frame_support::sp_io::TestExternalities::default().execute_with(|| {
+        unhashed::put(b"b:fail", &());
+        let StepResult::Failed {
+            cursor,
+            steps_done: 0,
+        } = run(&Migrations::new(V1), 1, &mut |_| {})
+        else {
+            panic!("the step should fail");
+        };
+        // the step of `A` is rolled back along with the failed one of `B`
+        assert_eq!(unhashed::get::<u32>(b"a"), None);
+        assert_eq!(Migrations::describe_position(V1, &cursor), Ok("0; 0".into()));
+    });
+}
+
+#[test]
+fn parts_upgrade_their_own_cursor() {
+    // persisted with the schema 0 of both parts, `B` holding a `u8`
+    let mut cursor = 1u16.encode();
+    cursor.push(0);
+    Some(MigrateA { done: 2 }.encode()).encode_to(&mut cursor);
+    Some(vec![0u8]).encode_to(&mut cursor);
+
+    assert!(Migrations::validate_cursor(V1, &cursor));
+    assert_eq!(Migrations::describe_position(V1, &cursor), Ok("2; 0".into()));
+}
+
+#[test]
+fn checkpoints_hold_both_parts() {
+    frame_support::sp_io::TestExternalities::default().execute_with(|| {
+        // run directly, as the checkpoints of transactional steps are not taken
+        let mut step = Parallel::<MigrateA, MigrateB>::default();
+
+        let mut checkpoints = Vec::new();
+        step.step_with_checkpoint(&mut |step| checkpoints.push(step.describe_position()));
+        assert_eq!(checkpoints, vec!["1; 0".to_string()]);
+        assert_eq!(step.describe_position(), "1; finished");
+    });
+}
@@ -0,0 +1,60 @@
+use frame_support::traits::StorageVersion;
+use migratable::{GuardStep, Guarded, MigrateSequence, NoopMigration};
+
+use crate::common::MigrateTo;
+
+type Migrations = (NoopMigration<2>, MigrateTo<3>, NoopMigration<4>, NoopMigration<5>);
+
+#[test]
+fn upgrade_can_start_mid_sequence() {
+    let supported = |in_storage, target| {
+        Migrations::is_upgrade_supported(
+            StorageVersion::new(in_storage),
+            StorageVersion::new(target),
+        )
+    };
+
+    // from the bottom of the sequence
+    assert!(supported(1, 5));
+    // from the middle of it
+    assert!(supported(2, 5));
+    assert!(supported(3, 5));
+    assert!(supported(4, 5));
+    // capped below the last version
+    assert!(supported(3, 4));
+
+    // below or past the sequence
+    assert!(!supported(0, 5));
+    assert!(!supported(5, 5));
+    // beyond the last version
+    assert!(!supported(3, 6));
+    // below the version following the storage
+    assert!(!supported(4, 3));
+    assert!(!supported(3, 2));
+}
+
+#[test]
+fn sequence_starts_at_the_next_version() {
+    // a chain at v2 starts with the step of v3
+    assert!(Migrations::try_new(StorageVersion::new(3)).is_ok());
+    assert!(Migrations::try_new(StorageVersion::new(6)).is_err());
+}
+
+struct NotReady;
+
+impl GuardStep for NotReady {
+    fn check() -> Result<(), &'static str> {
+        Err("Not ready")
+    }
+}
+
+#[test]
+fn guard_is_not_checked_mid_sequence() {
+    type Migrations = Guarded<NotReady, (NoopMigration<2>, MigrateTo<3>, NoopMigration<4>)>;
+
+    assert_eq!(Migrations::check_dependencies(StorageVersion::new(2)), Err("Not ready"));
+    // a migration starting past the first version is not guarded
+    for version in 3..=4 {
+        assert_eq!(Migrations::check_dependencies(StorageVersion::new(version)), Ok(()));
+    }
+}
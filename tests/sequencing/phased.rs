@@ -0,0 +1,80 @@
+use frame_support::{traits::StorageVersion, weights::Weight};
+use migratable::{IsFinished, MigrateSequence, MigrationPhases, PhasedMigration, StepResult};
+use parity_scale_codec::{Decode, Encode};
+
+const STEP_WEIGHT: Weight = Weight::from_parts(10, 0);
+
+#[derive(Default, Debug, PartialEq, Encode, Decode)]
+enum Phases {
+    // copies 2 items, one per step
+    #[default]
+    Copy,
+    CopyFrom(u32),
+    DeleteOld,
+}
+
+impl MigrationPhases for Phases {
+    const MAX_CURSOR_LEN: u32 = 5;
+    fn max_step_weight() -> Weight {
+        STEP_WEIGHT
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        let finished = match self {
+            Self::Copy => {
+                *self = Self::CopyFrom(1);
+                IsFinished::No
+            }
+            Self::CopyFrom(_) | Self::DeleteOld => IsFinished::Yes,
+        };
+        (finished, STEP_WEIGHT)
+    }
+    fn next(&self) -> Option<Self> {
+        match self {
+            Self::Copy | Self::CopyFrom(_) => Some(Self::DeleteOld),
+            Self::DeleteOld => None,
+        }
+    }
+}
+
+type Migrations = (PhasedMigration<2, Phases>,);
+
+const V2: StorageVersion = StorageVersion::new(2);
+
+fn run(cursor: &[u8], steps: u64) -> StepResult {
+    let mut weight_left = STEP_WEIGHT.saturating_mul(steps);
+    Migrations::steps(V2, cursor, &mut weight_left, &mut |_| {})
+}
+
+/// Decodes the phase in progress from `cursor`, past its header.
+fn phase(cursor: &[u8]) -> Phases {
+    Phases::decode(&mut &cursor[3..]).unwrap()
+}
+
+#[test]
+fn phases_run_one_after_the_other() {
+    let StepResult::InProgress {
+        cursor,
+        steps_done: 1,
+    } = run(&Migrations::new(V2), 1)
+    else {
+        panic!("the migration should be in progress");
+    };
+    assert_eq!(phase(&cursor), Phases::CopyFrom(1));
+
+    // finishing a phase moves on to the next one within the same step
+    let StepResult::InProgress {
+        cursor,
+        steps_done: 1,
+    } = run(&cursor, 1)
+    else {
+        panic!("the migration should be in progress");
+    };
+    assert_eq!(phase(&cursor), Phases::DeleteOld);
+
+    assert_eq!(run(&cursor, 1), StepResult::Completed { steps_done: 1 });
+}
+
+#[test]
+fn last_phase_completes_the_version() {
+    assert_eq!(run(&Migrations::new(V2), 10), StepResult::Completed { steps_done: 3 });
+}
@@ -0,0 +1,49 @@
+use frame_support::{
+    traits::{GetStorageVersion, OnIdle, OnRuntimeUpgrade, StorageVersion},
+    weights::Weight,
+};
+use migratable::{IsFinished, MigrateSequence, MigrationStep, StepResult};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::common::{mock_runtime, pallet::Migration, MigrateTo};
+
+/// Only applies to other runtimes.
+#[derive(Default, Encode, Decode)]
+struct SkippedV2;
+
+impl MigrationStep for SkippedV2 {
+    const VERSION: u16 = 2;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::zero());
+    fn should_run() -> bool {
+        false
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        unreachable!("skipped versions are not stepped")
+    }
+}
+
+type Migrations = (MigrateTo<1>, SkippedV2, MigrateTo<3>);
+
+mock_runtime!(Migrations);
+
+#[test]
+fn skipped_version_completes_without_steps() {
+    let v2 = StorageVersion::new(2);
+    assert_eq!(
+        Migrations::steps(v2, &Migrations::new(v2), &mut Weight::MAX, &mut |_| {}),
+        StepResult::Completed { steps_done: 0 },
+    );
+}
+
+#[test]
+fn on_idle_migrates_past_a_skipped_version() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Example>();
+        Migration::<Test, false>::on_runtime_upgrade();
+
+        <Example as OnIdle<u64>>::on_idle(1, Weight::MAX);
+        assert_eq!(Example::on_chain_storage_version(), StorageVersion::new(3));
+        assert!(!Migration::<Test>::in_progress());
+    });
+}
@@ -0,0 +1,19 @@
+use migratable::accrue_steps;
+
+#[test]
+fn cumulative_steps_cross_the_u32_boundary() {
+    let mut total = 0u64;
+    // a call running `u32::MAX` steps, and a few more in the next block
+    total = accrue_steps(total, u32::MAX);
+    total = accrue_steps(total, 3);
+    assert_eq!(total, u64::from(u32::MAX) + 3);
+
+    // one step per block around the boundary
+    let mut total = u64::from(u32::MAX) - 1_000;
+    for _ in 0..2_000 {
+        total = accrue_steps(total, 1);
+    }
+    assert_eq!(total, u64::from(u32::MAX) + 1_000);
+
+    assert_eq!(accrue_steps(u64::MAX, 1), u64::MAX);
+}
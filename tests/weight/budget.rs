@@ -0,0 +1,36 @@
+use frame_support::{
+    traits::{Get, OnFinalize, OnInitialize},
+    weights::{constants::RocksDbWeight, Weight},
+};
+use migratable::budget::MigrationWeightBudget;
+
+use crate::common::{mock_runtime, MigrateTo};
+
+mock_runtime!((MigrateTo<1>,));
+
+type Budget = MigrationWeightBudget<Test>;
+
+#[test]
+fn uncapped_budget_writes_nothing() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(Budget::on_initialize(1), RocksDbWeight::get().reads(2));
+        Budget::consume(Weight::from_parts(10, 0));
+        assert_eq!(Budget::remaining(), None);
+        assert_eq!(Budget::clip(Weight::MAX), Weight::MAX);
+    });
+}
+
+#[test]
+fn capped_budget_is_cleared_at_the_end_of_the_block() {
+    new_test_ext().execute_with(|| {
+        let cap = Weight::from_parts(100, 0);
+        Budget::set_cap(Some(cap));
+        assert_eq!(Budget::on_initialize(1), RocksDbWeight::get().reads_writes(2, 1));
+
+        Budget::consume(Weight::from_parts(30, 0));
+        assert_eq!(Budget::clip(Weight::MAX), Weight::from_parts(70, 0));
+
+        Budget::on_finalize(1);
+        assert_eq!(Budget::remaining(), Some(cap));
+    });
+}
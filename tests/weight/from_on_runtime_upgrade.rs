@@ -0,0 +1,64 @@
+use frame_support::{
+    parameter_types,
+    traits::{OnRuntimeUpgrade, StorageVersion},
+    weights::Weight,
+};
+use migratable::{FromOnRuntimeUpgrade, IntegrityError, MigrateSequence, StepResult};
+use std::cell::Cell;
+
+const UPGRADE_WEIGHT: Weight = Weight::from_parts(1_000, 100);
+
+thread_local! {
+    static UPGRADES: Cell<u32> = const { Cell::new(0) };
+}
+
+struct MigrateToV2;
+
+impl OnRuntimeUpgrade for MigrateToV2 {
+    fn on_runtime_upgrade() -> Weight {
+        UPGRADES.with(|upgrades| upgrades.set(upgrades.get() + 1));
+        UPGRADE_WEIGHT
+    }
+}
+
+parameter_types! {
+    pub const MigrateToV2Weight: Weight = UPGRADE_WEIGHT;
+}
+
+type Migrations = (FromOnRuntimeUpgrade<2, MigrateToV2, MigrateToV2Weight>,);
+
+#[test]
+fn upgrade_runs_in_a_single_step() {
+    let v2 = StorageVersion::new(2);
+    let mut weight_left = UPGRADE_WEIGHT;
+    assert_eq!(
+        Migrations::steps(v2, &Migrations::new(v2), &mut weight_left, &mut |_| {}),
+        StepResult::Completed { steps_done: 1 },
+    );
+    assert_eq!(UPGRADES.with(Cell::get), 1);
+    assert_eq!(weight_left, Weight::zero());
+}
+
+#[test]
+fn upgrade_waits_for_its_max_weight() {
+    let v2 = StorageVersion::new(2);
+    let cursor = Migrations::new(v2);
+    let mut weight_left = UPGRADE_WEIGHT.saturating_sub(Weight::from_parts(1, 0));
+    assert!(matches!(
+        Migrations::steps(v2, &cursor, &mut weight_left, &mut |_| {}),
+        StepResult::InProgress { steps_done: 0, .. },
+    ));
+    assert_eq!(UPGRADES.with(Cell::get), 0);
+}
+
+#[test]
+fn max_weight_is_checked_against_the_block() {
+    assert_eq!(
+        Migrations::integrity_check(Weight::from_parts(999, 100), 1024),
+        vec![IntegrityError::RefTimeTooHigh {
+            version: 2,
+            ref_time: 1_000,
+            max: 999
+        }],
+    );
+}
@@ -0,0 +1,14 @@
+//! The weight accounting of the migrations: step limits, the overhead of `migrate`, `on_idle`,
+//! the shared budget and the simulation of the blocks a migration takes.
+
+#[path = "../common/mod.rs"]
+mod common;
+
+mod budget;
+mod from_on_runtime_upgrade;
+mod max_step_weight;
+mod on_idle;
+mod reserve_for_user;
+mod simulation;
+mod steps_per_block;
+mod version_advance;
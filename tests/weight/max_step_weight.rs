@@ -0,0 +1,64 @@
+use frame_support::{traits::StorageVersion, weights::Weight};
+use migratable::{IntegrityError, IsFinished, MigrateSequence, MigrationStep, StepResult};
+use parity_scale_codec::{Decode, Encode};
+
+#[derive(Default, Encode, Decode)]
+struct MigrateV2;
+
+impl MigrationStep for MigrateV2 {
+    const VERSION: u16 = 2;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(Weight::from_parts(1_000, 100));
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+}
+
+// defines neither `MAX_STEP_WEIGHT` nor `max_step_weight`
+#[derive(Default, Encode, Decode)]
+struct MigrateV3;
+
+impl MigrationStep for MigrateV3 {
+    const VERSION: u16 = 3;
+    const MAX_CURSOR_LEN: u32 = 0;
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, Weight::zero())
+    }
+}
+
+const MAX_BLOCK_WEIGHT: Weight = Weight::from_parts(1_000_000, 1_000_000);
+
+#[test]
+fn max_step_weight_is_a_constant() {
+    const WEIGHT: Option<Weight> = <MigrateV2 as MigrationStep>::MAX_STEP_WEIGHT;
+    assert_eq!(WEIGHT, Some(Weight::from_parts(1_000, 100)));
+    assert!(<(MigrateV2,)>::integrity_check(MAX_BLOCK_WEIGHT, 1024).is_empty());
+}
+
+#[test]
+fn missing_max_step_weight_is_reported() {
+    assert_eq!(
+        <(MigrateV2, MigrateV3)>::integrity_check(MAX_BLOCK_WEIGHT, 1024),
+        vec![IntegrityError::MissingMaxStepWeight { version: 3 }],
+    );
+}
+
+#[test]
+fn step_runs_when_the_weight_left_equals_max_step_weight() {
+    let v2 = StorageVersion::new(2);
+    let cursor = <(MigrateV2,)>::new(v2);
+    let max_step_weight = Weight::from_parts(1_000, 100);
+
+    let mut weight_left = max_step_weight;
+    assert_eq!(
+        <(MigrateV2,)>::steps_checked(v2, &cursor, &mut weight_left, 0, &mut |_| {}),
+        Ok(StepResult::Completed { steps_done: 1 }),
+    );
+
+    // one unit short on a single dimension
+    let mut weight_left = max_step_weight.saturating_sub(Weight::from_parts(0, 1));
+    assert!(matches!(
+        <(MigrateV2,)>::steps_checked(v2, &cursor, &mut weight_left, 0, &mut |_| {}),
+        Ok(StepResult::InProgress { steps_done: 0, .. }),
+    ));
+}
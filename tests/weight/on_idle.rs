@@ -0,0 +1,107 @@
+use frame_support::{
+    traits::{Get, OnIdle, OnRuntimeUpgrade, StorageVersion},
+    weights::{constants::RocksDbWeight, Weight},
+};
+use migratable::{
+    weights::{SubstrateWeight, WeightInfo},
+    IsFinished, MigrationStatus, MigrationStep,
+};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::common::{mock_runtime, pallet::Migration};
+
+const STEP_WEIGHT: Weight = Weight::from_parts(1_000_000, 0);
+
+/// Never completes, so that the migration takes all the weight it is given.
+#[derive(Default, Encode, Decode)]
+struct MigrateV1;
+
+impl MigrationStep for MigrateV1 {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(STEP_WEIGHT);
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::No, STEP_WEIGHT)
+    }
+}
+
+mock_runtime!((MigrateV1,));
+
+fn start() {
+    StorageVersion::new(0).put::<Example>();
+    Migration::<Test, false>::on_runtime_upgrade();
+}
+
+#[test]
+fn step_consuming_exactly_the_budget_is_fully_reported() {
+    // the overhead of `migrate`, a single step and the reservation for the version advance and
+    // the no-op versions after it
+    let weight_limit = Migration::<Test>::migrate_overhead()
+        .saturating_add(STEP_WEIGHT)
+        .saturating_add(migratable::version_advance_weight(RocksDbWeight::get()))
+        .saturating_add(RocksDbWeight::get().writes(1));
+    // along with the existence check of `on_idle`
+    let remaining_weight = weight_limit.saturating_add(RocksDbWeight::get().reads(1));
+
+    let migrate_weight = new_test_ext().execute_with(|| {
+        start();
+        let (_, weight) = Migration::<Test>::migrate(weight_limit);
+        weight
+    });
+    // the reservation is left, as the version is not completed
+    assert_eq!(migrate_weight, Migration::<Test>::migrate_overhead().saturating_add(STEP_WEIGHT));
+    new_test_ext().execute_with(|| {
+        start();
+        let weight = <Example as OnIdle<u64>>::on_idle(1, remaining_weight);
+        assert_eq!(crate::common::pallet::MigrationStepsDone::<Test>::get(), 1);
+        // everything `migrate` reports, within the remaining weight
+        assert_eq!(weight, RocksDbWeight::get().reads(1).saturating_add(migrate_weight));
+        assert!(weight.all_lte(remaining_weight));
+    });
+}
+
+#[test]
+fn idle_blocks_after_the_migrations_cost_a_single_read() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(1).put::<Example>();
+        assert_eq!(Migration::<Test>::status(), MigrationStatus::Completed);
+
+        let weight = <Example as OnIdle<u64>>::on_idle(1, Weight::MAX);
+        // instead of the overhead of a `migrate` call finding no migration
+        assert_eq!(weight, RocksDbWeight::get().reads(1));
+        assert!(weight.all_lt(SubstrateWeight::<Test>::migration_noop()));
+    });
+}
+
+#[test]
+fn tiny_idle_budget_skips_migrate() {
+    new_test_ext().execute_with(|| {
+        start();
+        let status = Migration::<Test>::status();
+
+        let remaining_weight =
+            Migration::<Test>::migrate_overhead().saturating_sub(Weight::from_parts(1, 0));
+        let weight = <Example as OnIdle<u64>>::on_idle(1, remaining_weight);
+        assert_eq!(weight, RocksDbWeight::get().reads(1));
+        assert_eq!(crate::common::pallet::MigrationStepsDone::<Test>::get(), 0);
+        assert_eq!(Migration::<Test>::status(), status);
+    });
+}
+
+#[test]
+fn status_follows_the_migration() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Example>();
+        assert_eq!(Migration::<Test>::status(), MigrationStatus::NotStarted);
+
+        Migration::<Test, false>::on_runtime_upgrade();
+        // the version tag and the schema byte of the cursor
+        assert_eq!(
+            Migration::<Test>::status(),
+            MigrationStatus::InProgress {
+                version: 1,
+                cursor_len: 3
+            },
+        );
+    });
+}
@@ -0,0 +1,94 @@
+use frame_support::{
+    traits::{OnIdle, OnRuntimeUpgrade, StorageVersion},
+    weights::Weight,
+};
+use migratable::{with_reserved_weight, IsFinished, MigrationStep};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::common::mock_runtime;
+
+/// Consumes all the weight it is given, like a migration with plenty of steps left.
+fn greedy_migration(remaining_weight: &mut Weight) -> Weight {
+    core::mem::replace(remaining_weight, Weight::zero())
+}
+
+#[test]
+fn migrations_leave_the_reservation_to_the_user() {
+    let mut remaining_weight = Weight::from_parts(100, 100);
+    let migration_weight =
+        with_reserved_weight(&mut remaining_weight, Weight::from_parts(30, 10), greedy_migration);
+    assert_eq!(migration_weight, Weight::from_parts(70, 90));
+    // the user's hook runs afterwards with the reservation
+    assert_eq!(remaining_weight, Weight::from_parts(30, 10));
+}
+
+#[test]
+fn reservation_is_capped_at_the_remaining_weight() {
+    let mut remaining_weight = Weight::from_parts(20, 100);
+    let migration_weight =
+        with_reserved_weight(&mut remaining_weight, Weight::from_parts(30, 10), greedy_migration);
+    assert_eq!(migration_weight, Weight::from_parts(0, 90));
+    assert_eq!(remaining_weight, Weight::from_parts(20, 10));
+}
+
+/// Never completes, so that the migration takes all the weight it is given.
+#[derive(Default, Encode, Decode)]
+struct MigrateV1;
+
+impl MigrationStep for MigrateV1 {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(STEP_WEIGHT);
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::No, STEP_WEIGHT)
+    }
+}
+
+const STEP_WEIGHT: Weight = Weight::from_parts(1_000_000_000, 0);
+const RESERVED: Weight = Weight::from_parts(3_000_000_000, 0);
+
+#[migratable::pallet]
+#[frame_support::pallet]
+pub mod pallet {
+    use frame_support::pallet_prelude::*;
+    use frame_system::pallet_prelude::*;
+
+    #[migratable::config]
+    #[pallet::config]
+    pub trait Config: frame_system::Config {}
+
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+    #[pallet::pallet]
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T>(_);
+
+    /// The weight left to the body of `on_idle`.
+    #[pallet::storage]
+    pub type UserWeight<T> = StorageValue<_, Weight, OptionQuery>;
+
+    #[migratable::hooks(reserve_for_user = super::RESERVED)]
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        fn on_idle(_block: BlockNumberFor<T>, mut remaining_weight: Weight) -> Weight {
+            UserWeight::<T>::put(remaining_weight);
+            Weight::zero()
+        }
+    }
+}
+
+mock_runtime!((MigrateV1,), pallet);
+
+#[test]
+fn on_idle_leaves_the_reservation_to_the_user() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(0).put::<Example>();
+        pallet::Migration::<Test, false>::on_runtime_upgrade();
+
+        let remaining_weight = Weight::from_parts(100_000_000_000, 100_000);
+        <Example as OnIdle<u64>>::on_idle(1, remaining_weight);
+        // the migration ran, but the body still got the reservation
+        assert!(pallet::MigrationStepsDone::<Test>::get() > 0);
+        assert!(pallet::UserWeight::<Test>::get().unwrap().all_gte(RESERVED));
+    });
+}
@@ -0,0 +1,94 @@
+use frame_support::weights::Weight;
+use migratable::{
+    simulation::{simulate, BlockPlan, SimulationInput},
+    IsFinished, MigrationStep,
+};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::common::MigrateTo;
+
+const STEP_WEIGHT: Weight = Weight::from_parts(10, 0);
+
+#[derive(Default, Encode, Decode)]
+struct MigrateV1;
+
+impl MigrationStep for MigrateV1 {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(STEP_WEIGHT);
+    fn max_steps_per_block() -> Option<u32> {
+        Some(2)
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, STEP_WEIGHT)
+    }
+}
+
+#[derive(Default, Encode, Decode)]
+struct MigrateV2;
+
+impl MigrationStep for MigrateV2 {
+    const VERSION: u16 = 2;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(STEP_WEIGHT);
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::Yes, STEP_WEIGHT)
+    }
+}
+
+fn block(block: u32, steps: &[(u16, u32)]) -> BlockPlan {
+    BlockPlan {
+        block,
+        steps: steps.to_vec(),
+    }
+}
+
+#[test]
+fn plan_follows_the_weight_and_the_steps_cap() {
+    let input = SimulationInput {
+        steps_per_version: vec![(1, 5), (2, 3)],
+        block_weight: STEP_WEIGHT.mul(3),
+        max_blocks: 10,
+    };
+    let plan = simulate::<(MigrateV1, MigrateV2)>(1, 2, Weight::zero(), &input);
+    assert_eq!(
+        plan,
+        vec![
+            // v1 yields at its cap of two steps per block
+            block(0, &[(1, 2)]),
+            block(1, &[(1, 2)]),
+            // the steps of v2 don't count towards the cap of v1
+            block(2, &[(1, 1), (2, 2)]),
+            block(3, &[(2, 1)]),
+        ],
+    );
+}
+
+#[test]
+fn plan_stops_at_the_last_version() {
+    let input = SimulationInput {
+        steps_per_version: vec![],
+        block_weight: STEP_WEIGHT,
+        max_blocks: 10,
+    };
+    let plan = simulate::<(MigrateTo<65535>,)>(65535, 65535, Weight::zero(), &input);
+    assert_eq!(plan, vec![block(0, &[(65535, 1)])]);
+}
+
+#[test]
+fn plan_stops_after_max_blocks() {
+    let input = SimulationInput {
+        steps_per_version: vec![(2, 100)],
+        block_weight: STEP_WEIGHT,
+        max_blocks: 3,
+    };
+    let plan = simulate::<(MigrateV2,)>(2, 2, Weight::zero(), &input);
+    assert_eq!(
+        plan,
+        vec![
+            block(0, &[(2, 1)]),
+            block(1, &[(2, 1)]),
+            block(2, &[(2, 1)])
+        ]
+    );
+}
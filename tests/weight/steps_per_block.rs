@@ -0,0 +1,44 @@
+use frame_support::{traits::StorageVersion, weights::Weight};
+use migratable::{IsFinished, MigrateSequence, MigrationStep, StepResult};
+use parity_scale_codec::{Decode, Encode};
+
+const STEP_WEIGHT: Weight = Weight::from_parts(1_000, 0);
+
+#[derive(Default, Encode, Decode)]
+struct MigrateV1;
+
+impl MigrationStep for MigrateV1 {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 0;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(STEP_WEIGHT);
+    fn max_steps_per_block() -> Option<u32> {
+        Some(3)
+    }
+    fn step(&mut self) -> (IsFinished, Weight) {
+        (IsFinished::No, STEP_WEIGHT)
+    }
+}
+
+type Migrations = (MigrateV1,);
+
+fn run(steps_in_block: u32) -> StepResult {
+    let version = StorageVersion::new(1);
+    migratable::steps_with_reserve::<Migrations>(
+        version,
+        &Migrations::new(version),
+        &mut Weight::MAX,
+        steps_in_block,
+        Weight::zero(),
+        &mut |_| {},
+    )
+    .unwrap()
+}
+
+#[test]
+fn steps_of_previous_calls_count_towards_the_cap() {
+    assert!(matches!(run(0), StepResult::Yielded { steps_done: 3, .. }));
+    assert!(matches!(run(2), StepResult::Yielded { steps_done: 1, .. }));
+    // a call made once the cap is reached runs no step
+    assert!(matches!(run(3), StepResult::Yielded { steps_done: 0, .. }));
+    assert!(matches!(run(u32::MAX), StepResult::Yielded { steps_done: 0, .. }));
+}
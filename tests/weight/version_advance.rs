@@ -0,0 +1,126 @@
+use frame_support::{
+    traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+    weights::{constants::RocksDbWeight, Weight},
+};
+use migratable::{
+    IsFinished, MigrateError, MigrateResult, MigrateSequence, MigrationStep, NoopMigration,
+    StepResult,
+};
+use parity_scale_codec::{Decode, Encode};
+
+use crate::common::{mock_runtime, pallet::Migration};
+
+const STEP_WEIGHT: Weight = Weight::from_parts(10, 0);
+
+/// Completes in three steps.
+#[derive(Default, Encode, Decode)]
+struct MigrateV1 {
+    steps: u8,
+}
+
+impl MigrationStep for MigrateV1 {
+    const VERSION: u16 = 1;
+    const MAX_CURSOR_LEN: u32 = 1;
+    const MAX_STEP_WEIGHT: Option<Weight> = Some(STEP_WEIGHT);
+    fn step(&mut self) -> (IsFinished, Weight) {
+        self.steps += 1;
+        if self.steps == 3 {
+            (IsFinished::Yes, STEP_WEIGHT)
+        } else {
+            (IsFinished::No, STEP_WEIGHT)
+        }
+    }
+}
+
+type Migrations = (MigrateV1, NoopMigration<2>);
+
+mock_runtime!(Migrations);
+
+fn run(weight_limit: Weight) -> (StepResult, Weight) {
+    let version = StorageVersion::new(1);
+    let mut weight_left = weight_limit;
+    let result = migratable::steps_with_reserve::<Migrations>(
+        version,
+        &Migrations::new(version),
+        &mut weight_left,
+        0,
+        migratable::version_advance_weight(RocksDbWeight::get()),
+        &mut |_| {},
+    )
+    .unwrap();
+    (result, weight_left)
+}
+
+#[test]
+fn version_advance_fits_at_the_boundary() {
+    let advance_weight = migratable::version_advance_weight(RocksDbWeight::get());
+
+    // the last step and the version advance fit exactly
+    let (result, weight_left) = run(STEP_WEIGHT.mul(3).saturating_add(advance_weight));
+    assert_eq!(result, StepResult::Completed { steps_done: 3 });
+    assert_eq!(weight_left, advance_weight);
+
+    // one unit short, the last step is left for the next call
+    let weight_limit = STEP_WEIGHT
+        .mul(3)
+        .saturating_add(advance_weight)
+        .saturating_sub(Weight::from_parts(1, 0));
+    let (result, weight_left) = run(weight_limit);
+    assert!(matches!(result, StepResult::InProgress { steps_done: 2, .. }));
+    assert_eq!(weight_left, advance_weight.saturating_add(Weight::from_parts(9, 0)));
+}
+
+#[test]
+fn migrate_reserves_the_version_advance() {
+    let advance_weight = migratable::version_advance_weight(RocksDbWeight::get());
+    // the overhead of `migrate`, the three steps, the version advance and the no-op v2 after it
+    let weight_limit = Migration::<Test>::migrate_overhead()
+        .saturating_add(STEP_WEIGHT.mul(3))
+        .saturating_add(advance_weight)
+        .saturating_add(RocksDbWeight::get().writes(1));
+    let start = || {
+        StorageVersion::new(0).put::<Example>();
+        Migration::<Test, false>::on_runtime_upgrade();
+    };
+
+    // one unit short, the last step is left for the next call
+    new_test_ext().execute_with(|| {
+        start();
+        let (result, _) =
+            Migration::<Test>::migrate(weight_limit.saturating_sub(Weight::from_parts(1, 0)));
+        assert_eq!(result, MigrateResult::InProgress { steps_done: 2 });
+        assert_eq!(Example::on_chain_storage_version(), StorageVersion::new(0));
+    });
+
+    // the last step and the version advance fit exactly
+    new_test_ext().execute_with(|| {
+        start();
+        let (result, weight) = Migration::<Test>::migrate(weight_limit);
+        assert_eq!(result, MigrateResult::Completed);
+        // the bookkeeping is charged out of the limit
+        assert_eq!(weight, weight_limit);
+        assert_eq!(Example::on_chain_storage_version(), StorageVersion::new(2));
+    });
+}
+
+#[test]
+fn migrate_stops_at_the_last_storage_version() {
+    assert_eq!(migratable::next_version(StorageVersion::new(u16::MAX)), None);
+
+    new_test_ext().execute_with(|| {
+        StorageVersion::new(u16::MAX).put::<Example>();
+        let cursor = Migrations::new(StorageVersion::new(1));
+        crate::common::pallet::MigrationInProgress::<Test>::put(cursor.clone());
+
+        let (result, _) = Migration::<Test>::migrate(Weight::MAX);
+        assert_eq!(
+            result,
+            MigrateResult::Failed {
+                reason: MigrateError::UnsupportedVersion(u16::MAX).as_str(),
+            },
+        );
+        // kept, rather than unlocking the calls
+        assert_eq!(crate::common::pallet::MigrationInProgress::<Test>::get(), Some(cursor));
+        assert_eq!(Example::on_chain_storage_version(), StorageVersion::new(u16::MAX));
+    });
+}